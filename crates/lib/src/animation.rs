@@ -0,0 +1,499 @@
+use glam::{Mat4, Quat, Vec3};
+
+use crate::scene::InstanceHandle;
+
+/// Node of the scene graph, as imported from glTF.
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: Option<String>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    /// World transform, updated by [`crate::Scene::update_node_transforms`].
+    pub world: Mat4,
+    /// Instances created for the primitives of the node mesh.
+    pub instances: Vec<InstanceHandle>,
+    /// Skin deforming the node mesh, in which case the node transform is
+    /// ignored.
+    pub skin: Option<usize>,
+    /// Morph target weights of the node mesh, empty if it has no targets.
+    pub weights: Vec<f32>,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self {
+            name: None,
+            parent: None,
+            children: Vec::new(),
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            world: Mat4::IDENTITY,
+            instances: Vec::new(),
+            skin: None,
+            weights: Vec::new(),
+        }
+    }
+}
+
+impl Node {
+    pub fn local(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    CubicSpline,
+}
+
+/// Keyframe values of a channel.
+///
+/// With cubic spline interpolation, each keyframe stores an in-tangent, a
+/// value and an out-tangent, in that order.
+#[derive(Clone, Debug)]
+pub enum ChannelValues {
+    Translation(Vec<Vec3>),
+    Rotation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+    /// Morph target weights, one value per target for each keyframe.
+    Weights(Vec<f32>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub node: usize,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub values: ChannelValues,
+}
+
+impl Channel {
+    /// Returns the keyframes surrounding `time` and the blend factor.
+    fn keyframes(&self, time: f32) -> (usize, usize, f32) {
+        let last = self.times.len() - 1;
+        if time <= self.times[0] {
+            return (0, 0, 0.0);
+        }
+        if time >= self.times[last] {
+            return (last, last, 0.0);
+        }
+        let next = self.times.partition_point(|&t| t <= time);
+        let prev = next - 1;
+        let duration = self.times[next] - self.times[prev];
+        (prev, next, (time - self.times[prev]) / duration)
+    }
+
+    fn sample_vec3(&self, values: &[Vec3], time: f32) -> Vec3 {
+        let (prev, next, t) = self.keyframes(time);
+        match self.interpolation {
+            Interpolation::Step => values[prev],
+            Interpolation::Linear => values[prev].lerp(values[next], t),
+            Interpolation::CubicSpline => {
+                let dt = self.times[next] - self.times[prev];
+                let (v0, b0) = (values[prev * 3 + 1], values[prev * 3 + 2]);
+                let (a1, v1) = (values[next * 3], values[next * 3 + 1]);
+                hermite(v0, b0 * dt, v1, a1 * dt, t)
+            }
+        }
+    }
+
+    fn sample_quat(&self, values: &[Quat], time: f32) -> Quat {
+        let (prev, next, t) = self.keyframes(time);
+        match self.interpolation {
+            Interpolation::Step => values[prev],
+            Interpolation::Linear => values[prev].slerp(values[next], t),
+            Interpolation::CubicSpline => {
+                let dt = self.times[next] - self.times[prev];
+                let (v0, b0) = (values[prev * 3 + 1], values[prev * 3 + 2]);
+                let (a1, v1) = (values[next * 3], values[next * 3 + 1]);
+                let q = hermite(
+                    glam::Vec4::from(v0),
+                    glam::Vec4::from(b0) * dt,
+                    glam::Vec4::from(v1),
+                    glam::Vec4::from(a1) * dt,
+                    t,
+                );
+                Quat::from_vec4(q).normalize()
+            }
+        }
+    }
+
+    fn sample_weights(&self, values: &[f32], time: f32, weights: &mut Vec<f32>) {
+        let (prev, next, t) = self.keyframes(time);
+        let count = match self.interpolation {
+            Interpolation::CubicSpline => values.len() / (self.times.len() * 3),
+            _ => values.len() / self.times.len(),
+        };
+        weights.resize(count, 0.0);
+        for (i, weight) in weights.iter_mut().enumerate() {
+            let value = |keyframe: usize| values[keyframe * count + i];
+            *weight = match self.interpolation {
+                Interpolation::Step => value(prev),
+                Interpolation::Linear => value(prev) + (value(next) - value(prev)) * t,
+                Interpolation::CubicSpline => {
+                    let dt = self.times[next] - self.times[prev];
+                    let (v0, b0) = (value(prev * 3 + 1), value(prev * 3 + 2));
+                    let (a1, v1) = (value(next * 3), value(next * 3 + 1));
+                    hermite(v0, b0 * dt, v1, a1 * dt, t)
+                }
+            };
+        }
+    }
+
+    /// Writes the channel value at `time` into the targeted node.
+    pub fn apply(&self, time: f32, nodes: &mut [Node]) {
+        if self.times.is_empty() {
+            return;
+        }
+        let Some(node) = nodes.get_mut(self.node) else {
+            return;
+        };
+        match &self.values {
+            ChannelValues::Translation(v) => node.translation = self.sample_vec3(v, time),
+            ChannelValues::Rotation(v) => node.rotation = self.sample_quat(v, time),
+            ChannelValues::Scale(v) => node.scale = self.sample_vec3(v, time),
+            ChannelValues::Weights(v) => self.sample_weights(v, time, &mut node.weights),
+        }
+    }
+}
+
+fn hermite<T>(v0: T, b0: T, v1: T, a1: T, t: f32) -> T
+where
+    T: std::ops::Mul<f32, Output = T> + std::ops::Add<Output = T>,
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+    v0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + b0 * (t3 - 2.0 * t2 + t)
+        + v1 * (-2.0 * t3 + 3.0 * t2)
+        + a1 * (t3 - t2)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Animation {
+    pub name: Option<String>,
+    pub channels: Vec<Channel>,
+    /// Time of the last keyframe, in seconds.
+    pub duration: f32,
+}
+
+impl Animation {
+    pub fn apply(&self, time: f32, nodes: &mut [Node]) {
+        for channel in &self.channels {
+            channel.apply(time, nodes);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Skin {
+    pub joints: Vec<usize>,
+    pub inverse_bind_matrices: Vec<Mat4>,
+}
+
+impl Skin {
+    pub fn joint_matrices(&self, nodes: &[Node]) -> Vec<Mat4> {
+        self.joints
+            .iter()
+            .enumerate()
+            .map(|(i, &joint)| {
+                let inverse_bind = self
+                    .inverse_bind_matrices
+                    .get(i)
+                    .copied()
+                    .unwrap_or(Mat4::IDENTITY);
+                nodes[joint].world * inverse_bind
+            })
+            .collect()
+    }
+}
+
+/// Mesh deformed by a skin, with its vertices in bind pose.
+#[derive(Clone, Debug)]
+pub struct SkinnedMesh {
+    pub blas_index: u32,
+    pub skin: usize,
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f32; 4]>,
+    pub positions: Vec<[f32; 4]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+}
+
+impl SkinnedMesh {
+    /// Skins the bind pose, writing world-space positions and normals.
+    pub fn deform(
+        &self,
+        joint_matrices: &[Mat4],
+        positions: &mut [[f32; 4]],
+        normals: Option<&mut Vec<[f32; 3]>>,
+    ) {
+        let skin_matrix = |i: usize| {
+            let (joints, weights) = (self.joints[i], self.weights[i]);
+            (0..4).fold(Mat4::ZERO, |m, j| {
+                match joint_matrices.get(joints[j] as usize) {
+                    Some(joint) => m + *joint * weights[j],
+                    None => m,
+                }
+            })
+        };
+
+        for (i, position) in positions.iter_mut().enumerate() {
+            let p = self.positions[i];
+            let p = skin_matrix(i).transform_point3(Vec3::new(p[0], p[1], p[2]));
+            *position = [p.x, p.y, p.z, 0.0];
+        }
+        if let (Some(normals), Some(rest)) = (normals, &self.normals) {
+            for (i, normal) in normals.iter_mut().enumerate() {
+                let n = skin_matrix(i)
+                    .transform_vector3(Vec3::from(rest[i]))
+                    .normalize_or_zero();
+                *normal = n.into();
+            }
+        }
+    }
+}
+
+/// Displacements of a morph target, one per vertex.
+#[derive(Clone, Debug, Default)]
+pub struct MorphTarget {
+    pub positions: Option<Vec<Vec3>>,
+    pub normals: Option<Vec<Vec3>>,
+}
+
+/// Mesh deformed by morph targets, with its base vertices.
+#[derive(Clone, Debug)]
+pub struct MorphedMesh {
+    pub blas_index: u32,
+    pub targets: Vec<MorphTarget>,
+    pub weights: Vec<f32>,
+    /// Set by [`crate::Scene::set_morph_weights`], the animated node weights
+    /// are then ignored.
+    pub overridden: bool,
+    pub positions: Vec<[f32; 4]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+}
+
+impl MorphedMesh {
+    /// Blends the targets displacements into the base vertices.
+    pub fn morph(&self, positions: &mut [[f32; 4]], normals: Option<&mut Vec<[f32; 3]>>) {
+        let weighted = || {
+            self.targets
+                .iter()
+                .zip(self.weights.iter().copied())
+                .filter(|(_, weight)| *weight != 0.0)
+        };
+
+        for (i, position) in positions.iter_mut().enumerate() {
+            let base = self.positions[i];
+            let p =
+                weighted().fold(
+                    Vec3::new(base[0], base[1], base[2]),
+                    |p, (target, w)| match &target.positions {
+                        Some(displacements) => p + displacements[i] * w,
+                        None => p,
+                    },
+                );
+            *position = [p.x, p.y, p.z, 0.0];
+        }
+        if let (Some(normals), Some(base)) = (normals, &self.normals) {
+            for (i, normal) in normals.iter_mut().enumerate() {
+                let n = weighted().fold(Vec3::from(base[i]), |n, (target, w)| {
+                    match &target.normals {
+                        Some(displacements) => n + displacements[i] * w,
+                        None => n,
+                    }
+                });
+                *normal = n.normalize_or_zero().into();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(interpolation: Interpolation, times: &[f32], values: ChannelValues) -> Channel {
+        Channel {
+            node: 0,
+            interpolation,
+            times: times.to_vec(),
+            values,
+        }
+    }
+
+    fn translation_at(channel: &Channel, time: f32) -> Vec3 {
+        let mut nodes = vec![Node::default()];
+        channel.apply(time, &mut nodes);
+        nodes[0].translation
+    }
+
+    #[test]
+    fn keyframes_clamp_outside_the_range() {
+        let c = channel(
+            Interpolation::Linear,
+            &[1.0, 2.0, 4.0],
+            ChannelValues::Translation(vec![Vec3::X, Vec3::Y, Vec3::Z]),
+        );
+        assert_eq!(c.keyframes(0.0), (0, 0, 0.0));
+        assert_eq!(c.keyframes(5.0), (2, 2, 0.0));
+        assert_eq!(c.keyframes(3.0), (1, 2, 0.5));
+        assert_eq!(translation_at(&c, -1.0), Vec3::X);
+        assert_eq!(translation_at(&c, 10.0), Vec3::Z);
+    }
+
+    #[test]
+    fn linear_and_step_sampling() {
+        let values = ChannelValues::Translation(vec![Vec3::ZERO, Vec3::new(2.0, 4.0, -2.0)]);
+        let linear = channel(Interpolation::Linear, &[0.0, 2.0], values.clone());
+        assert_eq!(translation_at(&linear, 1.0), Vec3::new(1.0, 2.0, -1.0));
+
+        let step = channel(Interpolation::Step, &[0.0, 2.0], values);
+        assert_eq!(translation_at(&step, 1.9), Vec3::ZERO);
+        assert_eq!(translation_at(&step, 2.0), Vec3::new(2.0, 4.0, -2.0));
+    }
+
+    #[test]
+    fn rotation_slerps() {
+        let end = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let c = channel(
+            Interpolation::Linear,
+            &[0.0, 1.0],
+            ChannelValues::Rotation(vec![Quat::IDENTITY, end]),
+        );
+        let mut nodes = vec![Node::default()];
+        c.apply(0.5, &mut nodes);
+        let expected = Quat::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        assert!(nodes[0].rotation.angle_between(expected) < 1e-4);
+    }
+
+    #[test]
+    fn cubic_spline_with_flat_tangents_is_smoothstep() {
+        // [in-tangent, value, out-tangent] per keyframe.
+        let c = channel(
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            ChannelValues::Translation(vec![
+                Vec3::splat(5.0),
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::ONE,
+                Vec3::splat(5.0),
+            ]),
+        );
+        for t in [0.0, 0.25, 0.5, 0.8, 1.0] {
+            let smoothstep = t * t * (3.0 - 2.0 * t);
+            let value = translation_at(&c, t * 2.0);
+            assert!((value - Vec3::splat(smoothstep)).abs().max_element() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn cubic_spline_uses_the_out_and_in_tangents() {
+        // Tangents of the neighbouring keys must be ignored.
+        let c = channel(
+            Interpolation::CubicSpline,
+            &[0.0, 1.0],
+            ChannelValues::Translation(vec![
+                Vec3::splat(100.0),
+                Vec3::ZERO,
+                Vec3::X,
+                Vec3::Y,
+                Vec3::ZERO,
+                Vec3::splat(100.0),
+            ]),
+        );
+        // Hermite basis at t = 0.5: out-tangent * 0.125, in-tangent * -0.125.
+        let value = translation_at(&c, 0.5);
+        assert!((value - Vec3::new(0.125, -0.125, 0.0)).abs().max_element() < 1e-5);
+    }
+
+    #[test]
+    fn cubic_spline_weights_layout() {
+        // Per keyframe: the in-tangents, values and out-tangents of both
+        // targets. Only the flat tangents between the keys are used.
+        let c = channel(
+            Interpolation::CubicSpline,
+            &[0.0, 1.0],
+            ChannelValues::Weights(vec![
+                9.0, 9.0, 0.0, 1.0, 0.0, 0.0, // First keyframe.
+                0.0, 0.0, 1.0, 0.0, 9.0, 9.0, // Second keyframe.
+            ]),
+        );
+        let mut nodes = vec![Node::default()];
+        c.apply(0.25, &mut nodes);
+        let smoothstep = 0.15625;
+        assert_eq!(nodes[0].weights.len(), 2);
+        assert!((nodes[0].weights[0] - smoothstep).abs() < 1e-6);
+        assert!((nodes[0].weights[1] - (1.0 - smoothstep)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn morph_blends_weighted_targets() {
+        let mesh = MorphedMesh {
+            blas_index: 0,
+            targets: vec![
+                MorphTarget {
+                    positions: Some(vec![Vec3::X, Vec3::ZERO]),
+                    normals: Some(vec![Vec3::new(0.0, -2.0, 0.0); 2]),
+                },
+                MorphTarget {
+                    positions: Some(vec![Vec3::Y, Vec3::new(0.0, 0.0, 4.0)]),
+                    normals: None,
+                },
+            ],
+            weights: vec![0.5, 0.25],
+            overridden: false,
+            positions: vec![[0.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0]],
+            normals: Some(vec![[0.0, 0.0, 1.0]; 2]),
+        };
+        let mut positions = vec![[0.0; 4]; 2];
+        let mut normals = vec![[0.0; 3]; 2];
+        mesh.morph(&mut positions, Some(&mut normals));
+        assert_eq!(positions[0], [0.5, 0.25, 0.0, 0.0]);
+        assert_eq!(positions[1], [1.0, 1.0, 2.0, 0.0]);
+        let expected = Vec3::new(0.0, -1.0, 1.0).normalize();
+        assert!((Vec3::from(normals[0]) - expected).length() < 1e-6);
+    }
+
+    #[test]
+    fn skin_blends_two_joints() {
+        let mut nodes = vec![Node::default(), Node::default()];
+        nodes[1].world = Mat4::from_translation(Vec3::new(2.0, 0.0, 0.0));
+        let skin = Skin {
+            joints: vec![0, 1],
+            // The second joint is bound one unit up.
+            inverse_bind_matrices: vec![
+                Mat4::IDENTITY,
+                Mat4::from_translation(Vec3::new(0.0, -1.0, 0.0)),
+            ],
+        };
+        let joint_matrices = skin.joint_matrices(&nodes);
+        assert_eq!(
+            joint_matrices[1],
+            Mat4::from_translation(Vec3::new(2.0, -1.0, 0.0))
+        );
+
+        let mesh = SkinnedMesh {
+            blas_index: 0,
+            skin: 0,
+            joints: vec![[0, 1, 0, 0], [1, 0, 0, 0]],
+            weights: vec![[0.5, 0.5, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]],
+            positions: vec![[0.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0]],
+            normals: Some(vec![[0.0, 1.0, 0.0]; 2]),
+        };
+        let mut positions = vec![[0.0; 4]; 2];
+        let mut normals = vec![[0.0; 3]; 2];
+        mesh.deform(&joint_matrices, &mut positions, Some(&mut normals));
+        assert_eq!(positions[0], [1.0, -0.5, 0.0, 0.0]);
+        assert_eq!(positions[1], [2.0, 0.0, 0.0, 0.0]);
+        assert_eq!(normals, vec![[0.0, 1.0, 0.0]; 2]);
+    }
+}
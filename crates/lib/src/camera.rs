@@ -0,0 +1,256 @@
+use glam::{Mat4, Vec2, Vec3};
+
+/// Mapping from the image plane to primary ray directions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// Pinhole or thin lens, using [`CameraParams::v_fov`].
+    Perspective,
+    /// Parallel rays along the view axis.
+    Orthographic {
+        /// Vertical extent of the view, in world units.
+        height: f32,
+    },
+    /// Full sphere in latitude / longitude layout, for 360° panoramas.
+    Equirectangular,
+    /// Equidistant fisheye.
+    Fisheye {
+        /// Field of view across the image height, in radians.
+        fov: f32,
+    },
+    /// Omnidirectional stereo, an equirectangular view of one eye.
+    ///
+    /// Ray origins sit on a circle whose diameter is the interpupillary
+    /// distance.
+    OmniStereo {
+        /// Signed distance from the center to the eye, negative for the left
+        /// eye.
+        eye_offset: f32,
+    },
+}
+
+impl Projection {
+    /// Id of the projection in the ray generation shader.
+    pub(crate) fn id(&self) -> u32 {
+        match self {
+            Projection::Perspective => 0,
+            Projection::Orthographic { .. } => 1,
+            Projection::Equirectangular => 2,
+            Projection::Fisheye { .. } => 3,
+            Projection::OmniStereo { .. } => 4,
+        }
+    }
+
+    /// Extra parameter of the projection in the ray generation shader.
+    pub(crate) fn scale(&self) -> f32 {
+        match *self {
+            Projection::Orthographic { height } => height,
+            Projection::Fisheye { fov } => fov,
+            Projection::OmniStereo { eye_offset } => eye_offset,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Thin-lens camera model used for ray generation and reprojection.
+///
+/// With a null aperture, the camera is a pinhole and everything is in focus.
+/// Depth of field is only simulated with the perspective projection.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraParams {
+    pub projection: Projection,
+    /// Vertical field of view, in radians.
+    pub v_fov: f32,
+    /// Width over height of the sensor, `None` to match the viewport.
+    ///
+    /// When it differs from the viewport, the vertical field of view is kept
+    /// and the image is stretched horizontally.
+    pub aspect_ratio: Option<f32>,
+    pub near: f32,
+    pub far: f32,
+    /// Radius of the lens, in world units.
+    pub aperture: f32,
+    /// Distance from the lens to the plane in focus, along the view axis.
+    pub focus_distance: f32,
+    /// Number of diaphragm blades shaping the bokeh, circular below 3.
+    pub blades: u32,
+}
+
+impl Default for CameraParams {
+    fn default() -> Self {
+        Self {
+            projection: Projection::Perspective,
+            v_fov: 60_f32.to_radians(),
+            aspect_ratio: None,
+            near: 0.01,
+            far: 100.0,
+            aperture: 0.0,
+            focus_distance: 1.0,
+            blades: 0,
+        }
+    }
+}
+
+impl CameraParams {
+    pub fn aspect_ratio(&self, dimensions: (u32, u32)) -> f32 {
+        self.aspect_ratio
+            .unwrap_or(dimensions.0 as f32 / dimensions.1.max(1) as f32)
+    }
+
+    /// World space ray going through the center of the lens, for `uv` in
+    /// `[0, 1]` with the origin at the top left of the viewport.
+    pub fn primary_ray(
+        &self,
+        view_to_world: &Mat4,
+        dimensions: (u32, u32),
+        uv: Vec2,
+    ) -> (Vec3, Vec3) {
+        // Mirrors `render/shaders/ray_generation.wgsl`.
+        let aspect_ratio = self.aspect_ratio(dimensions);
+        let ndc = Vec2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+        let right = view_to_world.x_axis.truncate();
+        let up = view_to_world.y_axis.truncate();
+        let forward = view_to_world.z_axis.truncate();
+        let eye = view_to_world.w_axis.truncate();
+        match self.projection {
+            Projection::Perspective => {
+                let tan_half_fov = (self.v_fov * 0.5).tan();
+                let dir = forward
+                    + right * (ndc.x * tan_half_fov * aspect_ratio)
+                    + up * (ndc.y * tan_half_fov);
+                (eye, dir.normalize())
+            }
+            Projection::Orthographic { height } => {
+                let half = height * 0.5;
+                let origin = eye + right * (ndc.x * half * aspect_ratio) + up * (ndc.y * half);
+                (origin, forward)
+            }
+            Projection::Equirectangular | Projection::OmniStereo { .. } => {
+                let phi = (uv.x - 0.5) * std::f32::consts::TAU;
+                let theta = (0.5 - uv.y) * std::f32::consts::PI;
+                let dir = forward * (theta.cos() * phi.cos())
+                    + right * (theta.cos() * phi.sin())
+                    + up * theta.sin();
+                let offset = (right * phi.cos() - forward * phi.sin()) * self.projection.scale();
+                (eye + offset, dir.normalize())
+            }
+            Projection::Fisheye { fov } => {
+                let p = Vec2::new(ndc.x * aspect_ratio, ndc.y);
+                let r = p.length();
+                if r <= f32::EPSILON {
+                    return (eye, forward);
+                }
+                let theta = (r * fov * 0.5).min(std::f32::consts::PI);
+                let side = (right * p.x + up * p.y) / r;
+                (
+                    eye,
+                    (forward * theta.cos() + side * theta.sin()).normalize(),
+                )
+            }
+        }
+    }
+
+    /// View to clip space matrix, for a view space looking down +Z.
+    ///
+    /// `None` for non-linear projections, which can't be reprojected.
+    pub fn projection(&self, dimensions: (u32, u32)) -> Option<Mat4> {
+        let aspect_ratio = self.aspect_ratio(dimensions);
+        match self.projection {
+            Projection::Perspective => Some(Mat4::perspective_lh(
+                self.v_fov,
+                aspect_ratio,
+                self.near,
+                self.far,
+            )),
+            Projection::Orthographic { height } => {
+                let half = height * 0.5;
+                Some(Mat4::orthographic_lh(
+                    -half * aspect_ratio,
+                    half * aspect_ratio,
+                    -half,
+                    half,
+                    self.near,
+                    self.far,
+                ))
+            }
+            Projection::Equirectangular
+            | Projection::Fisheye { .. }
+            | Projection::OmniStereo { .. } => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraProjection {
+    Perspective {
+        /// Vertical field of view, in radians.
+        y_fov: f32,
+        /// Width over height, `None` to use the one of the viewport.
+        aspect_ratio: Option<f32>,
+        z_near: f32,
+        /// `None` for an infinite projection.
+        z_far: Option<f32>,
+    },
+    Orthographic {
+        /// Half width of the view volume.
+        x_mag: f32,
+        /// Half height of the view volume.
+        y_mag: f32,
+        z_near: f32,
+        z_far: f32,
+    },
+}
+
+/// Camera placed in the scene, as imported from glTF.
+#[derive(Clone, Debug)]
+pub struct SceneCamera {
+    pub name: Option<String>,
+    /// Node holding the camera, `None` if it isn't part of the hierarchy.
+    pub node: Option<usize>,
+    pub projection: CameraProjection,
+    /// World transform, updated by [`crate::Scene::update_node_transforms`].
+    ///
+    /// The camera looks down its local -Z axis.
+    pub world: Mat4,
+}
+
+impl SceneCamera {
+    pub fn origin(&self) -> Vec3 {
+        self.world.w_axis.truncate()
+    }
+
+    pub fn direction(&self) -> Vec3 {
+        (-self.world.z_axis.truncate()).normalize_or_zero()
+    }
+
+    /// Camera model matching the glTF projection.
+    pub fn params(&self) -> CameraParams {
+        match self.projection {
+            CameraProjection::Perspective {
+                y_fov,
+                aspect_ratio,
+                z_near,
+                z_far,
+            } => CameraParams {
+                v_fov: y_fov,
+                aspect_ratio,
+                near: z_near,
+                far: z_far.unwrap_or(CameraParams::default().far),
+                ..Default::default()
+            },
+            CameraProjection::Orthographic {
+                x_mag,
+                y_mag,
+                z_near,
+                z_far,
+            } => CameraParams {
+                projection: Projection::Orthographic {
+                    height: y_mag * 2.0,
+                },
+                aspect_ratio: Some(x_mag / y_mag),
+                near: z_near,
+                far: z_far,
+                ..Default::default()
+            },
+        }
+    }
+}
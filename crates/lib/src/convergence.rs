@@ -0,0 +1,99 @@
+use glam::Mat4;
+
+use crate::camera::CameraParams;
+use crate::device::Device;
+use crate::errors::Error;
+use crate::renderer::Renderer;
+
+/// When to stop accumulating frames of an offline render.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Convergence {
+    /// Maximum number of accumulated frames.
+    pub max_samples: u32,
+    /// Stops earlier once the relative mean change of the radiance between
+    /// two checks falls below this value.
+    pub noise_threshold: Option<f32>,
+    /// Number of frames between two noise checks.
+    pub check_interval: u32,
+}
+
+impl Convergence {
+    /// Accumulates exactly `samples` frames.
+    pub fn samples(samples: u32) -> Self {
+        Self {
+            max_samples: samples,
+            noise_threshold: None,
+            check_interval: 16,
+        }
+    }
+}
+
+impl Default for Convergence {
+    fn default() -> Self {
+        Self::samples(64)
+    }
+}
+
+/// Mean absolute difference between two radiance buffers, relative to the
+/// mean of `current`. Alpha is ignored.
+fn relative_change(previous: &[f32], current: &[f32]) -> f32 {
+    let mut diff = 0.0_f64;
+    let mut sum = 0.0_f64;
+    for (p, c) in previous.chunks_exact(4).zip(current.chunks_exact(4)) {
+        for i in 0..3 {
+            diff += (c[i] - p[i]).abs() as f64;
+            sum += c[i].abs() as f64;
+        }
+    }
+    if sum <= f64::EPSILON {
+        return 0.0;
+    }
+    (diff / sum) as f32
+}
+
+impl Renderer {
+    /// Restarts accumulation and renders frames until `convergence` is met.
+    ///
+    /// Frames are accumulated with the current blit mode, offline renders
+    /// should use [`crate::BlitMode::Pahtrace`]. Returns the number of
+    /// accumulated frames.
+    pub async fn converge(
+        &mut self,
+        device: &Device,
+        queue: &wgpu::Queue,
+        view_transform: &Mat4,
+        camera: &CameraParams,
+        convergence: &Convergence,
+    ) -> Result<u32, Error> {
+        let max_samples = convergence.max_samples.max(1);
+        let check_interval = convergence.check_interval.max(1);
+
+        self.reset_accumulation(queue);
+        let mut previous: Option<Vec<f32>> = None;
+        for sample in 1..=max_samples {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offline Encoder"),
+            });
+            self.queries.start_frame(queue.get_timestamp_period());
+            self.raytrace(&mut encoder, queue, view_transform, camera);
+            queue.submit(Some(encoder.finish()));
+            self.queries.end_frame(queue.get_timestamp_period());
+            self.accumulate = true;
+
+            let Some(threshold) = convergence.noise_threshold else {
+                continue;
+            };
+            if sample % check_interval != 0 {
+                continue;
+            }
+            let radiance = self.read_radiance(device, queue).await?;
+            if let Some(previous) = &previous {
+                if relative_change(previous, &radiance) < threshold {
+                    return Ok(sample);
+                }
+            }
+            previous = Some(radiance);
+        }
+        Ok(max_samples)
+    }
+}
@@ -2,7 +2,10 @@
 pub enum Error {
     FileNotFound(String),
     TextureToBufferReadFail,
+    BufferReadFail,
     AccelBuild(String),
+    InvalidLut(String),
+    AovUnavailable(String),
 }
 
 impl From<Error> for String {
@@ -12,9 +15,12 @@ impl From<Error> for String {
                 format!("file not found: {}", filename)
             }
             Error::TextureToBufferReadFail => String::from("failed to read pixels from GPU to CPU"),
+            Error::BufferReadFail => String::from("failed to read buffer from GPU to CPU"),
             Error::AccelBuild(reason) => {
                 format!("failed to build acceleration structure: {:?}", reason)
             }
+            Error::InvalidLut(reason) => format!("invalid LUT: {}", reason),
+            Error::AovUnavailable(reason) => format!("AOV unavailable: {}", reason),
         }
     }
 }
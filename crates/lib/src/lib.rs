@@ -1,11 +1,23 @@
+mod animation;
+mod camera;
+mod convergence;
 mod device;
 mod errors;
 pub mod loaders;
+mod lut;
+mod panorama;
 mod render;
 mod renderer;
 mod scene;
+mod tlas;
 
+pub use animation::*;
+pub use camera::*;
+pub use convergence::*;
 pub use device::*;
 pub use errors::*;
+pub use lut::*;
+pub use panorama::*;
 pub use renderer::*;
 pub use scene::*;
+pub use tlas::*;
@@ -1,7 +1,7 @@
-use albedo_rtx::{uniforms, Material, MeshDescriptor, Vertex};
+use albedo_rtx::{uniforms, Material, Vertex};
 use std::{convert::TryInto, io::Read};
 
-use crate::Scene;
+use crate::{MeshData, Scene};
 
 pub fn load_binary_from_path<P: AsRef<std::path::Path>>(path: P, scene: &mut Scene) {
     let f = std::fs::File::open(path).unwrap();
@@ -46,25 +46,26 @@ pub fn load_binary_from_path<P: AsRef<std::path::Path>>(path: P, scene: &mut Sce
         vertices[i as usize + 2].normal = normal;
     }
 
-    let mesh = MeshDescriptor {
-        positions: pas::Slice::new(&vertices, 0),
-        normals: Some(pas::Slice::new(&vertices, 16)),
-        texcoords0: None,
+    let mesh = MeshData {
+        positions: vertices.iter().map(|v| v.position).collect(),
+        normals: Some(
+            vertices
+                .iter()
+                .map(|v| [v.normal[0], v.normal[1], v.normal[2]])
+                .collect(),
+        ),
+        texcoords: None,
+        indices: None,
     };
 
-    let blas_index = scene.blas.entries.len() as u32;
-    let material_index = scene.materials.len() as u32;
-
-    scene.blas.add_bvh(mesh);
-    scene
-        .blas
-        .add_instance(blas_index, glam::Mat4::IDENTITY, material_index);
-
-    scene.materials.push(Material {
+    let blas_index = scene.add_mesh(mesh);
+    let material = scene.add_material(Material {
         color: glam::Vec4::new(1.0, 1.0, 1.0, 1.0),
         roughness: 1.0,
         reflectivity: 0.0,
         albedo_texture: uniforms::INVALID_INDEX,
         mra_texture: uniforms::INVALID_INDEX,
     });
+    scene.add_instance(blas_index, glam::Mat4::IDENTITY, material);
+    scene.update_tlas();
 }
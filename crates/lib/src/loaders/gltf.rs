@@ -2,12 +2,26 @@ use std::path::Path;
 
 use albedo_rtx::uniforms;
 
-use albedo_rtx::IndexedMeshDescriptor;
-use albedo_rtx::MeshDescriptor;
+use gltf::animation::util::ReadOutputs;
 use gltf::{self, image};
 
+use crate::animation::{
+    Animation, Channel, ChannelValues, Interpolation, MorphTarget, MorphedMesh, Node, Skin,
+    SkinnedMesh,
+};
+use crate::camera::{CameraProjection, SceneCamera};
 use crate::errors::Error;
-use crate::scene::{ImageData, Scene};
+use crate::scene::{ImageData, MaterialHandle, MeshData, Scene};
+
+/// Joint indices and weights of each vertex.
+type SkinAttributes = (Vec<[u16; 4]>, Vec<[f32; 4]>);
+
+struct ImportedPrimitive {
+    blas_index: u32,
+    material: Option<usize>,
+    skinning: Option<SkinAttributes>,
+    targets: Vec<MorphTarget>,
+}
 
 fn rgba8_image(image: image::Data) -> ImageData {
     let (components, _) = match image.format {
@@ -57,8 +71,10 @@ pub fn load_gltf(data: &[u8], scene: &mut Scene) -> Result<(), Error> {
         }
     };
 
-    let bvh_offset = scene.blas.entries.len() as u32;
+    // Triangle primitives of each mesh.
+    let mut mesh_primitives: Vec<Vec<ImportedPrimitive>> = Vec::with_capacity(doc.meshes().len());
     for mesh in doc.meshes() {
+        let mut primitives = Vec::new();
         for primitive in mesh.primitives() {
             let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
             let Some(in_positions) = reader.read_positions() else {
@@ -87,66 +103,240 @@ pub fn load_gltf(data: &[u8], scene: &mut Scene) -> Result<(), Error> {
                 } else {
                     None
                 };
+            let indices: Option<Vec<u32>> = reader
+                .read_indices()
+                .map(|indices| indices.into_u32().collect());
 
-            let mesh = MeshDescriptor {
-                positions: pas::Slice::native(&positions),
-                normals: normals.as_ref().map(|v| pas::Slice::native(v)),
-                texcoords0: texcoords.as_ref().map(|v| pas::Slice::native(&v)),
+            let skinning = match (reader.read_joints(0), reader.read_weights(0)) {
+                (Some(joints), Some(weights)) => {
+                    Some((joints.into_u16().collect(), weights.into_f32().collect()))
+                }
+                _ => None,
             };
 
-            if let Some(indices) = reader.read_indices() {
-                let indices: Vec<u32> = indices.into_u32().collect();
-                scene.blas.add_bvh_indexed(IndexedMeshDescriptor {
-                    mesh,
-                    indices: &indices,
-                });
-            } else {
-                scene.blas.add_bvh(mesh);
-            }
+            let targets: Vec<MorphTarget> = reader
+                .read_morph_targets()
+                .map(|(positions, normals, _)| MorphTarget {
+                    positions: positions.map(|p| p.map(glam::Vec3::from).collect()),
+                    normals: normals.map(|n| n.map(glam::Vec3::from).collect()),
+                })
+                .collect();
+
+            let blas_index = scene.add_mesh(MeshData {
+                positions,
+                normals,
+                texcoords,
+                indices,
+            });
+            primitives.push(ImportedPrimitive {
+                blas_index,
+                material: primitive.material().index(),
+                skinning,
+                targets,
+            });
         }
+        mesh_primitives.push(primitives);
     }
 
     let mat_offset = scene.materials.len() as u32;
-    let texture_offset = scene.images.len() as u32;
+    // Materials reference images, in the order of the texture atlas.
+    let image_offset = scene.images.len() as u32;
     for material in doc.materials() {
         let pbr = material.pbr_metallic_roughness();
-        scene.materials.push(uniforms::Material {
+        scene.add_material(uniforms::Material {
             color: pbr.base_color_factor().into(),
             roughness: pbr.roughness_factor(),
             reflectivity: pbr.metallic_factor(),
             albedo_texture: pbr
                 .base_color_texture()
-                .map(|c| texture_offset + c.texture().index() as u32)
+                .map(|c| image_offset + c.texture().source().index() as u32)
                 .unwrap_or(uniforms::INVALID_INDEX),
             mra_texture: pbr
                 .metallic_roughness_texture()
-                .map(|c| texture_offset + c.texture().index() as u32)
+                .map(|c| image_offset + c.texture().source().index() as u32)
                 .unwrap_or(uniforms::INVALID_INDEX),
             ..Default::default()
         });
     }
 
+    let node_offset = scene.nodes.len();
+    let skin_offset = scene.skins.len();
     for node in doc.nodes() {
-        // @todo: handle scene graph.
-        // User should have their own scene graph. However, a simple code path
-        // should directly be provided for users.
-        if let Some(mesh) = node.mesh() {
-            let index = mesh.index() as u32;
-            let model_to_world = glam::Mat4::from_cols_array_2d(&node.transform().matrix());
-            for primitive in mesh.primitives() {
-                let material_index = match primitive.material().index() {
-                    Some(v) => v as u32,
-                    None => u32::MAX,
-                };
-                scene.blas.add_instance(
-                    bvh_offset + index,
-                    model_to_world,
-                    mat_offset + material_index,
-                );
+        let (translation, rotation, scale) = node.transform().decomposed();
+        scene.nodes.push(Node {
+            name: node.name().map(String::from),
+            translation: translation.into(),
+            rotation: glam::Quat::from_array(rotation),
+            scale: scale.into(),
+            skin: node.skin().map(|skin| skin_offset + skin.index()),
+            ..Default::default()
+        });
+    }
+    for node in doc.nodes() {
+        let Some(camera) = node.camera() else {
+            continue;
+        };
+        let projection = match camera.projection() {
+            gltf::camera::Projection::Perspective(p) => CameraProjection::Perspective {
+                y_fov: p.yfov(),
+                aspect_ratio: p.aspect_ratio(),
+                z_near: p.znear(),
+                z_far: p.zfar(),
+            },
+            gltf::camera::Projection::Orthographic(o) => CameraProjection::Orthographic {
+                x_mag: o.xmag(),
+                y_mag: o.ymag(),
+                z_near: o.znear(),
+                z_far: o.zfar(),
+            },
+        };
+        scene.cameras.push(SceneCamera {
+            name: camera.name().or(node.name()).map(String::from),
+            node: Some(node_offset + node.index()),
+            projection,
+            world: glam::Mat4::IDENTITY,
+        });
+    }
+    for node in doc.nodes() {
+        let parent = node_offset + node.index();
+        for child in node.children() {
+            let child = node_offset + child.index();
+            scene.nodes[parent].children.push(child);
+            scene.nodes[child].parent = Some(parent);
+        }
+    }
+    for skin in doc.skins() {
+        let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+        let inverse_bind_matrices = reader
+            .read_inverse_bind_matrices()
+            .map(|matrices| {
+                matrices
+                    .map(|m| glam::Mat4::from_cols_array_2d(&m))
+                    .collect()
+            })
+            .unwrap_or_default();
+        scene.skins.push(Skin {
+            joints: skin.joints().map(|j| node_offset + j.index()).collect(),
+            inverse_bind_matrices,
+        });
+    }
+    scene.update_node_transforms();
+
+    for node in doc.nodes() {
+        let Some(mesh) = node.mesh() else {
+            continue;
+        };
+        let index = node_offset + node.index();
+        let skin = scene.nodes[index].skin;
+        // Node weights override the default ones of the mesh.
+        if let Some(weights) = node.weights().or(mesh.weights()) {
+            scene.nodes[index].weights = weights.to_vec();
+        }
+        // Skinned vertices are transformed to world space by the joints.
+        let model_to_world = match skin {
+            Some(_) => glam::Mat4::IDENTITY,
+            None => scene.nodes[index].world,
+        };
+        for primitive in &mesh_primitives[mesh.index()] {
+            let material_index = match primitive.material {
+                Some(v) => mat_offset + v as u32,
+                None => u32::MAX,
+            };
+            let handle = scene.add_instance(
+                primitive.blas_index,
+                model_to_world,
+                MaterialHandle(material_index),
+            );
+            scene.nodes[index].instances.push(handle);
+
+            let blas_index = primitive.blas_index;
+            if !primitive.targets.is_empty()
+                && !scene
+                    .morphed_meshes
+                    .iter()
+                    .any(|m| m.blas_index == blas_index)
+            {
+                let base = scene.mesh(blas_index).unwrap();
+                scene.morphed_meshes.push(MorphedMesh {
+                    blas_index,
+                    targets: primitive.targets.clone(),
+                    weights: scene.nodes[index].weights.clone(),
+                    overridden: false,
+                    positions: base.positions.clone(),
+                    normals: base.normals.clone(),
+                });
+            }
+
+            let (Some(skin), Some((joints, weights))) = (skin, &primitive.skinning) else {
+                continue;
+            };
+            if scene
+                .skinned_meshes
+                .iter()
+                .any(|m| m.blas_index == blas_index)
+            {
+                // @todo: meshes shared by several skins must be duplicated.
+                continue;
             }
+            let bind_pose = scene.mesh(blas_index).unwrap();
+            scene.skinned_meshes.push(SkinnedMesh {
+                blas_index,
+                skin,
+                joints: joints.clone(),
+                weights: weights.clone(),
+                positions: bind_pose.positions.clone(),
+                normals: bind_pose.normals.clone(),
+            });
         }
     }
 
+    for animation in doc.animations() {
+        let mut channels = Vec::new();
+        let mut duration: f32 = 0.0;
+        for channel in animation.channels() {
+            let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+            let (Some(inputs), Some(outputs)) = (reader.read_inputs(), reader.read_outputs())
+            else {
+                continue;
+            };
+            let values = match outputs {
+                ReadOutputs::Translations(v) => {
+                    ChannelValues::Translation(v.map(glam::Vec3::from).collect())
+                }
+                ReadOutputs::Rotations(v) => {
+                    ChannelValues::Rotation(v.into_f32().map(glam::Quat::from_array).collect())
+                }
+                ReadOutputs::Scales(v) => ChannelValues::Scale(v.map(glam::Vec3::from).collect()),
+                ReadOutputs::MorphTargetWeights(v) => {
+                    ChannelValues::Weights(v.into_f32().collect())
+                }
+            };
+            let interpolation = match channel.sampler().interpolation() {
+                gltf::animation::Interpolation::Step => Interpolation::Step,
+                gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+            };
+            let times: Vec<f32> = inputs.collect();
+            duration = duration.max(times.last().copied().unwrap_or(0.0));
+            channels.push(Channel {
+                node: node_offset + channel.target().node().index(),
+                interpolation,
+                times,
+                values,
+            });
+        }
+        scene.animations.push(Animation {
+            name: animation.name().map(String::from),
+            channels,
+            duration,
+        });
+    }
+
+    // Deformed meshes are built in bind pose, apply the default weights and
+    // the rest pose.
+    scene.update_deformed_meshes();
+    scene.update_tlas();
+
     for image in images.into_iter() {
         // @todo: package metal / roughness / ao in single texture.
         scene.images.push(rgba8_image(image));
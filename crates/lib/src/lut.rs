@@ -0,0 +1,290 @@
+use crate::errors::Error;
+
+/// Size of the 3D texture 1D LUTs are baked into.
+const BAKED_1D_SIZE: u32 = 33;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LutKind {
+    /// One curve per channel.
+    OneD,
+    /// Full RGB cube.
+    ThreeD,
+}
+
+/// Color lookup table, as stored in Adobe / Resolve `.cube` files.
+#[derive(Clone, Debug)]
+pub struct Lut {
+    pub title: Option<String>,
+    pub kind: LutKind,
+    /// Entries per channel.
+    pub size: u32,
+    /// Input values mapped to the first and last entries.
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    /// For 3D LUTs, red varies the fastest, then green, then blue.
+    pub table: Vec<[f32; 3]>,
+}
+
+fn parse_floats<const N: usize>(values: &[&str], line: usize) -> Result<[f32; N], Error> {
+    if values.len() != N {
+        return Err(Error::InvalidLut(format!(
+            "line {}: expected {} values, got {}",
+            line,
+            N,
+            values.len()
+        )));
+    }
+    let mut result = [0.0; N];
+    for (value, text) in result.iter_mut().zip(values) {
+        *value = text
+            .parse()
+            .map_err(|_| Error::InvalidLut(format!("line {}: invalid number '{}'", line, text)))?;
+    }
+    Ok(result)
+}
+
+fn parse_size(values: &[&str], line: usize) -> Result<u32, Error> {
+    match values {
+        [size] => size
+            .parse()
+            .map_err(|_| Error::InvalidLut(format!("line {}: invalid size '{}'", line, size))),
+        _ => Err(Error::InvalidLut(format!("line {}: expected a size", line))),
+    }
+}
+
+impl Lut {
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|_| Error::FileNotFound(path.display().to_string()))?;
+        Self::parse(&text)
+    }
+
+    /// Parses the content of a `.cube` file.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut title = None;
+        let mut kind_and_size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let values: Vec<&str> = rest.split_whitespace().collect();
+            match keyword {
+                "TITLE" => title = Some(rest.trim().trim_matches('"').to_string()),
+                "LUT_1D_SIZE" => {
+                    kind_and_size = Some((LutKind::OneD, parse_size(&values, line_number)?))
+                }
+                "LUT_3D_SIZE" => {
+                    kind_and_size = Some((LutKind::ThreeD, parse_size(&values, line_number)?))
+                }
+                "DOMAIN_MIN" => domain_min = parse_floats(&values, line_number)?,
+                "DOMAIN_MAX" => domain_max = parse_floats(&values, line_number)?,
+                // Resolve variant, with the same range for every channel.
+                "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                    let [min, max] = parse_floats(&values, line_number)?;
+                    domain_min = [min; 3];
+                    domain_max = [max; 3];
+                }
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                    // Unknown keywords are allowed by the specification.
+                }
+                _ => {
+                    let entry: Vec<&str> = line.split_whitespace().collect();
+                    table.push(parse_floats(&entry, line_number)?);
+                }
+            }
+        }
+
+        let Some((kind, size)) = kind_and_size else {
+            return Err(Error::InvalidLut(String::from("missing LUT size")));
+        };
+        let (valid_size, expected) = match kind {
+            LutKind::OneD => ((2..=65536).contains(&size), size as usize),
+            LutKind::ThreeD => ((2..=256).contains(&size), (size as usize).pow(3)),
+        };
+        if !valid_size {
+            return Err(Error::InvalidLut(format!("unsupported size {}", size)));
+        }
+        if table.len() != expected {
+            return Err(Error::InvalidLut(format!(
+                "expected {} entries, got {}",
+                expected,
+                table.len()
+            )));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err(Error::InvalidLut(String::from("empty domain")));
+        }
+
+        Ok(Self {
+            title,
+            kind,
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    /// Samples the curve of `channel` of a 1D LUT, with `t` in [0, 1].
+    fn sample_1d(&self, channel: usize, t: f32) -> f32 {
+        let last = self.size as usize - 1;
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position as usize).min(last - 1);
+        let fract = position - index as f32;
+        let a = self.table[index][channel];
+        let b = self.table[index + 1][channel];
+        a + (b - a) * fract
+    }
+
+    /// Content of the 3D texture used for grading, and its size.
+    ///
+    /// 1D LUTs are baked into a cube.
+    pub(crate) fn texture_data(&self) -> (u32, Vec<[f32; 4]>) {
+        match self.kind {
+            LutKind::ThreeD => {
+                let data = self.table.iter().map(|c| [c[0], c[1], c[2], 1.0]).collect();
+                (self.size, data)
+            }
+            LutKind::OneD => {
+                let size = BAKED_1D_SIZE;
+                let step = 1.0 / (size - 1) as f32;
+                let mut data = Vec::with_capacity(size.pow(3) as usize);
+                for b in 0..size {
+                    for g in 0..size {
+                        for r in 0..size {
+                            data.push([
+                                self.sample_1d(0, r as f32 * step),
+                                self.sample_1d(1, g as f32 * step),
+                                self.sample_1d(2, b as f32 * step),
+                                1.0,
+                            ]);
+                        }
+                    }
+                }
+                (size, data)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Identity cube of side 2, red varying the fastest.
+    const IDENTITY_3D: &str = "0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+
+    #[test]
+    fn parse_1d() {
+        let text = "# Comment\nTITLE \"Warm curve\"\n\nLUT_1D_SIZE 3\n0 0 0\n0.6 0.5 0.4\n1 1 1\n";
+        let lut = Lut::parse(text).unwrap();
+        assert_eq!(lut.title.as_deref(), Some("Warm curve"));
+        assert_eq!(lut.kind, LutKind::OneD);
+        assert_eq!(lut.size, 3);
+        assert_eq!(lut.domain_min, [0.0; 3]);
+        assert_eq!(lut.domain_max, [1.0; 3]);
+        assert_eq!(lut.table, vec![[0.0; 3], [0.6, 0.5, 0.4], [1.0; 3]]);
+
+        let (size, data) = lut.texture_data();
+        assert_eq!(size, BAKED_1D_SIZE);
+        assert_eq!(data.len(), BAKED_1D_SIZE.pow(3) as usize);
+        assert_eq!(data[0], [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(*data.last().unwrap(), [1.0; 4]);
+    }
+
+    #[test]
+    fn parse_3d() {
+        let text = format!("LUT_3D_SIZE 2\n{}", IDENTITY_3D);
+        let lut = Lut::parse(&text).unwrap();
+        assert_eq!(lut.title, None);
+        assert_eq!(lut.kind, LutKind::ThreeD);
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.table.len(), 8);
+        assert_eq!(lut.table[1], [1.0, 0.0, 0.0]);
+        assert_eq!(lut.table[4], [0.0, 0.0, 1.0]);
+
+        let (size, data) = lut.texture_data();
+        assert_eq!(size, 2);
+        assert_eq!(data[2], [0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn parse_domain() {
+        let text = format!(
+            "DOMAIN_MIN -0.5 0 0.25\nDOMAIN_MAX 2 1 4\nLUT_3D_SIZE 2\n{}",
+            IDENTITY_3D
+        );
+        let lut = Lut::parse(&text).unwrap();
+        assert_eq!(lut.domain_min, [-0.5, 0.0, 0.25]);
+        assert_eq!(lut.domain_max, [2.0, 1.0, 4.0]);
+
+        let text = format!("LUT_3D_SIZE 2\nLUT_3D_INPUT_RANGE 0.1 8\n{}", IDENTITY_3D);
+        let lut = Lut::parse(&text).unwrap();
+        assert_eq!(lut.domain_min, [0.1; 3]);
+        assert_eq!(lut.domain_max, [8.0; 3]);
+
+        let text = "LUT_1D_INPUT_RANGE -1 1\nLUT_1D_SIZE 2\n0 0 0\n1 1 1\n";
+        let lut = Lut::parse(text).unwrap();
+        assert_eq!(lut.domain_min, [-1.0; 3]);
+        assert_eq!(lut.domain_max, [1.0; 3]);
+    }
+
+    #[test]
+    fn empty_domains_are_rejected() {
+        let flat_green = format!(
+            "DOMAIN_MIN 0 0.5 0\nDOMAIN_MAX 1 0.5 1\nLUT_3D_SIZE 2\n{}",
+            IDENTITY_3D
+        );
+        assert!(matches!(Lut::parse(&flat_green), Err(Error::InvalidLut(_))));
+        let reversed = format!("LUT_3D_INPUT_RANGE 1 0\nLUT_3D_SIZE 2\n{}", IDENTITY_3D);
+        assert!(matches!(Lut::parse(&reversed), Err(Error::InvalidLut(_))));
+        let two_values = "DOMAIN_MIN 0 0\nLUT_1D_SIZE 2\n0 0 0\n1 1 1\n";
+        assert!(matches!(Lut::parse(two_values), Err(Error::InvalidLut(_))));
+    }
+
+    #[test]
+    fn entry_count_must_match_the_size() {
+        let missing = Lut::parse("LUT_1D_SIZE 3\n0 0 0\n1 1 1\n");
+        assert!(
+            matches!(&missing, Err(Error::InvalidLut(reason)) if reason == "expected 3 entries, got 2")
+        );
+        let extra = format!("LUT_3D_SIZE 2\n{}1 1 1\n", IDENTITY_3D);
+        assert!(matches!(Lut::parse(&extra), Err(Error::InvalidLut(_))));
+        let truncated_entry = "LUT_1D_SIZE 2\n0 0 0\n1 1\n";
+        assert!(matches!(
+            Lut::parse(truncated_entry),
+            Err(Error::InvalidLut(_))
+        ));
+        assert!(matches!(
+            Lut::parse("0 0 0\n1 1 1\n"),
+            Err(Error::InvalidLut(_))
+        ));
+    }
+
+    #[test]
+    fn sizes_out_of_range_are_rejected() {
+        let single = Lut::parse("LUT_1D_SIZE 1\n0 0 0\n");
+        assert!(
+            matches!(&single, Err(Error::InvalidLut(reason)) if reason == "unsupported size 1")
+        );
+        for text in [
+            "LUT_3D_SIZE 1\n0 0 0\n",
+            "LUT_3D_SIZE 0\n",
+            "LUT_3D_SIZE 257\n",
+        ] {
+            assert!(matches!(Lut::parse(text), Err(Error::InvalidLut(_))));
+        }
+        assert!(matches!(
+            Lut::parse("LUT_3D_SIZE -2\n"),
+            Err(Error::InvalidLut(_))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+use glam::{Mat4, Vec3};
+
+use crate::camera::{CameraParams, Projection};
+use crate::convergence::Convergence;
+use crate::device::Device;
+use crate::errors::Error;
+use crate::renderer::{BlitMode, Renderer};
+use crate::scene::{ImageData, ProbeGPU, SceneGPU};
+
+/// Layout of the image produced by [`Renderer::render_panorama`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PanoramaLayout {
+    /// Omnidirectional stereo pair, left eye on top of the right eye.
+    ///
+    /// Each eye is an equirectangular image of `2 * size` by `size` pixels.
+    OmniStereoTopBottom {
+        /// Interpupillary distance, in world units.
+        ipd: f32,
+    },
+    /// The six faces of a cube in a horizontal strip, ordered
+    /// +X, -X, +Y, -Y, +Z, -Z, each `size` pixels wide.
+    ///
+    /// Faces are aligned with the world axes, only the camera position is
+    /// used.
+    CubemapStrip,
+}
+
+pub struct PanoramaDescriptor {
+    pub view_transform: Mat4,
+    pub camera: CameraParams,
+    pub layout: PanoramaLayout,
+    /// Height of an eye, or width of a cube face, in pixels.
+    pub size: u32,
+    /// Number of accumulated frames per view.
+    pub samples: u32,
+}
+
+struct PanoramaView {
+    view_transform: Mat4,
+    camera: CameraParams,
+    size: (u32, u32),
+    offset: (u32, u32),
+}
+
+impl PanoramaDescriptor {
+    fn output_size(&self) -> (u32, u32) {
+        match self.layout {
+            PanoramaLayout::OmniStereoTopBottom { .. } => (self.size * 2, self.size * 2),
+            PanoramaLayout::CubemapStrip => (self.size * 6, self.size),
+        }
+    }
+
+    fn views(&self) -> Vec<PanoramaView> {
+        let pinhole = CameraParams {
+            aperture: 0.0,
+            ..self.camera
+        };
+        match self.layout {
+            PanoramaLayout::OmniStereoTopBottom { ipd } => [-0.5, 0.5]
+                .iter()
+                .enumerate()
+                .map(|(i, side)| PanoramaView {
+                    view_transform: self.view_transform,
+                    camera: CameraParams {
+                        projection: Projection::OmniStereo {
+                            eye_offset: ipd * side,
+                        },
+                        aspect_ratio: None,
+                        ..pinhole
+                    },
+                    size: (self.size * 2, self.size),
+                    offset: (0, self.size * i as u32),
+                })
+                .collect(),
+            PanoramaLayout::CubemapStrip => {
+                let origin = self.view_transform.w_axis;
+                // (forward, up) of each face.
+                let faces = [
+                    (Vec3::X, Vec3::Y),
+                    (Vec3::NEG_X, Vec3::Y),
+                    (Vec3::Y, Vec3::Z),
+                    (Vec3::NEG_Y, Vec3::NEG_Z),
+                    (Vec3::Z, Vec3::Y),
+                    (Vec3::NEG_Z, Vec3::Y),
+                ];
+                faces
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(forward, up))| PanoramaView {
+                        view_transform: Mat4::from_cols(
+                            forward.cross(up).extend(0.0),
+                            up.extend(0.0),
+                            forward.extend(0.0),
+                            origin,
+                        ),
+                        camera: CameraParams {
+                            projection: Projection::Perspective,
+                            v_fov: 90_f32.to_radians(),
+                            aspect_ratio: Some(1.0),
+                            ..pinhole
+                        },
+                        size: (self.size, self.size),
+                        offset: (self.size * i as u32, 0),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Renderer {
+    /// Renders every view of a panorama with plain accumulation, and
+    /// stitches them into a single RGBA8 image.
+    ///
+    /// The renderer size and blit mode are restored afterwards.
+    pub async fn render_panorama(
+        &mut self,
+        device: &Device,
+        queue: &wgpu::Queue,
+        scene_resources: &SceneGPU,
+        probe: Option<&ProbeGPU>,
+        descriptor: &PanoramaDescriptor,
+    ) -> Result<ImageData, Error> {
+        let previous_size = self.target_size();
+        let previous_mode = self.blit_mode();
+        let previous_downsample = self.downsample_factor;
+        self.downsample_factor = 1.0;
+        self.set_blit_mode(BlitMode::Pahtrace);
+
+        let (width, height) = descriptor.output_size();
+        let mut output = vec![0_u8; width as usize * height as usize * 4];
+        let mut result = Ok(());
+        for view in descriptor.views() {
+            self.resize(device, scene_resources, probe, view.size);
+            let converged = self
+                .converge(
+                    device,
+                    queue,
+                    &view.view_transform,
+                    &view.camera,
+                    &Convergence::samples(descriptor.samples),
+                )
+                .await;
+            if let Err(e) = converged {
+                result = Err(e);
+                break;
+            }
+            let pixels = match self.read_pixels(device, queue).await {
+                Ok(pixels) => pixels,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            };
+            let row_bytes = view.size.0 as usize * 4;
+            for (y, row) in pixels.chunks_exact(row_bytes).enumerate() {
+                let start =
+                    ((view.offset.1 as usize + y) * width as usize + view.offset.0 as usize) * 4;
+                output[start..start + row_bytes].copy_from_slice(row);
+            }
+        }
+
+        // Restored first, the resize applies it.
+        self.downsample_factor = previous_downsample;
+        self.resize(device, scene_resources, probe, previous_size);
+        self.set_blit_mode(previous_mode);
+        self.reset_accumulation(queue);
+
+        result.map(|_| ImageData::new(output, width, height))
+    }
+}
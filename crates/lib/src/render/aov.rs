@@ -0,0 +1,195 @@
+use albedo_backend::gpu;
+
+use crate::renderer::Aov;
+
+const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+
+/// Format of the target the AOVs are decoded into.
+pub(crate) const AOV_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct AovUniforms {
+    aov: u32,
+    padding: [u32; 3],
+}
+
+/// Textures read by [`AovPass::dispatch`].
+pub(crate) struct AovSources<'a> {
+    pub gbuffer: &'a wgpu::TextureView,
+    pub motion: &'a wgpu::TextureView,
+    pub moments: &'a wgpu::TextureView,
+}
+
+/// Decodes the G-buffer, motion and moments of the denoiser into floats.
+pub(crate) struct AovPass {
+    bgl: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    uniforms: gpu::Buffer<AovUniforms>,
+    /// Instances and materials of the scene, to resolve the material ids.
+    scene: Option<(wgpu::Buffer, wgpu::Buffer)>,
+}
+
+fn texture_entry(binding: u32, sample_type: wgpu::TextureSampleType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+fn buffer_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+impl AovPass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let unfilterable = wgpu::TextureSampleType::Float { filterable: false };
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("AOV Layout"),
+            entries: &[
+                texture_entry(0, wgpu::TextureSampleType::Uint),
+                texture_entry(1, unfilterable),
+                texture_entry(2, unfilterable),
+                buffer_entry(3, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer_entry(4, wgpu::BufferBindingType::Storage { read_only: true }),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: AOV_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                buffer_entry(6, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("AOV Pipeline Layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("AOV Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/aov.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("AOV Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            bgl,
+            pipeline,
+            uniforms: gpu::Buffer::new_uniform(device, 1, None),
+            scene: None,
+        }
+    }
+
+    /// Must be called whenever the scene buffers are re-allocated.
+    pub fn set_scene(&mut self, instances: &wgpu::Buffer, materials: &wgpu::Buffer) {
+        self.scene = Some((instances.clone(), materials.clone()));
+    }
+
+    /// Decodes `aov` into `output`, an [`AOV_FORMAT`] texture of the render size.
+    ///
+    /// Does nothing if no scene was set.
+    pub fn dispatch(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        aov: Aov,
+        sources: &AovSources,
+        output: &wgpu::Texture,
+    ) {
+        let Some((instances, materials)) = &self.scene else {
+            return;
+        };
+        let code = match aov {
+            Aov::Beauty => 0,
+            Aov::Albedo => 1,
+            Aov::ShadingNormal => 2,
+            Aov::Depth => 3,
+            Aov::Motion => 4,
+            Aov::InstanceId => 5,
+            Aov::MaterialId => 6,
+            Aov::Variance => 7,
+        };
+        self.uniforms.update(
+            queue,
+            &[AovUniforms {
+                aov: code,
+                ..Default::default()
+            }],
+        );
+
+        let view = output.create_view(&wgpu::TextureViewDescriptor::default());
+        let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("AOV Bind Group"),
+            layout: &self.bgl,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(sources.gbuffer),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(sources.motion),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(sources.moments),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: instances.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: materials.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: self.uniforms.inner().as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("AOV Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bindgroup, &[]);
+        pass.dispatch_workgroups(
+            output.width().div_ceil(WORKGROUP_SIZE.0),
+            output.height().div_ceil(WORKGROUP_SIZE.1),
+            1,
+        );
+    }
+}
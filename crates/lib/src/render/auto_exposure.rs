@@ -0,0 +1,289 @@
+use std::sync::mpsc;
+
+use albedo_backend::gpu;
+use wgpu::util::DeviceExt;
+
+use crate::errors::Error;
+use crate::renderer::AutoExposure;
+
+/// Number of bins of the luminance histogram, including the bin of the
+/// pixels darker than the histogram range.
+pub(crate) const HISTOGRAM_BINS: usize = 256;
+
+const WORKGROUP_SIZE: (u32, u32) = (16, 16);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct ExposureParams {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    adaptation: f32,
+    low_percentile: f32,
+    high_percentile: f32,
+    reset: u32,
+    padding: [u32; 2],
+}
+
+/// GPU layout of [`AutoExposurePass::state`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct ExposureState {
+    pub bins: [u32; HISTOGRAM_BINS],
+    pub average_log_luminance: f32,
+    /// Linear scale applied by the tonemapper.
+    pub exposure: f32,
+}
+
+/// Builds a log-luminance histogram of the radiance, and adapts the
+/// exposure to its average.
+///
+/// The result stays on the GPU, in [`Self::state`].
+pub(crate) struct AutoExposurePass {
+    bgl: wgpu::BindGroupLayout,
+    build_pipeline: wgpu::ComputePipeline,
+    average_pipeline: wgpu::ComputePipeline,
+    params: gpu::Buffer<ExposureParams>,
+    state: wgpu::Buffer,
+    /// Copy of `state` mapped for the CPU, see [`Self::poll_state`].
+    readback: wgpu::Buffer,
+    /// Pending mapping of `readback`.
+    mapping: Option<mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    bindgroups: Vec<wgpu::BindGroup>,
+    reset: bool,
+}
+
+impl AutoExposurePass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Auto Exposure Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Auto Exposure Pipeline Layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Luminance Histogram Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("shaders/luminance_histogram.wgsl").into(),
+            ),
+        });
+        let create_pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Auto Exposure Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let build_pipeline = create_pipeline("build");
+        let average_pipeline = create_pipeline("average");
+
+        let state = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Auto Exposure State"),
+            contents: bytemuck::bytes_of(&ExposureState {
+                bins: [0; HISTOGRAM_BINS],
+                average_log_luminance: 0.0,
+                exposure: 1.0,
+            }),
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Auto Exposure Readback"),
+            size: state.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            bgl,
+            build_pipeline,
+            average_pipeline,
+            params: gpu::Buffer::new_uniform(device, 1, None),
+            state,
+            readback,
+            mapping: None,
+            bindgroups: Vec::new(),
+            reset: true,
+        }
+    }
+
+    /// Must be called whenever the render targets are re-created.
+    pub fn create_bind_groups(&mut self, device: &wgpu::Device, radiance: [&wgpu::TextureView; 2]) {
+        self.bindgroups = radiance
+            .iter()
+            .map(|view| self.create_source_bind_group(device, view))
+            .collect();
+    }
+
+    /// Bind group measuring `source`, for images other than the
+    /// accumulated radiance.
+    pub fn create_source_bind_group(
+        &self,
+        device: &wgpu::Device,
+        source: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Auto Exposure Bind Group"),
+            layout: &self.bgl,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.params.inner().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.state.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Bind group measuring the accumulated radiance at `index`.
+    pub fn accumulated(&self, index: usize) -> &wgpu::BindGroup {
+        &self.bindgroups[index]
+    }
+
+    /// Histogram and exposure, laid out as [`ExposureState`].
+    pub fn state(&self) -> &wgpu::Buffer {
+        &self.state
+    }
+
+    /// Reads back the state without waiting for the GPU.
+    ///
+    /// Each call copies the current state, and returns the copy started by a
+    /// previous call once it is mapped.
+    pub fn poll_state(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<Result<ExposureState, Error>> {
+        if let Some(mapping) = &self.mapping {
+            device.poll(wgpu::Maintain::Poll);
+            let state = match mapping.try_recv() {
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Ok(Ok(())) => {
+                    let state = {
+                        let bytes = self.readback.slice(..).get_mapped_range();
+                        bytemuck::pod_read_unaligned(&bytes)
+                    };
+                    self.readback.unmap();
+                    Ok(state)
+                }
+                _ => Err(Error::BufferReadFail),
+            };
+            self.mapping = None;
+            self.copy_state(device, queue);
+            return Some(state);
+        }
+        self.copy_state(device, queue);
+        None
+    }
+
+    fn copy_state(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Auto Exposure Readback Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&self.state, 0, &self.readback, 0, self.state.size());
+        queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = mpsc::channel();
+        self.readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.mapping = Some(receiver);
+    }
+
+    /// Jumps to the average of the next frame, without adaptation.
+    pub fn reset(&mut self) {
+        self.reset = true;
+    }
+
+    /// Writes the parameters of the next [`Self::dispatch`], adapting the
+    /// exposure over `delta` seconds.
+    pub fn update(&mut self, queue: &wgpu::Queue, settings: &AutoExposure, delta: f32) {
+        let range = (settings.max_log_luminance - settings.min_log_luminance).max(1e-3);
+        self.params.update(
+            queue,
+            &[ExposureParams {
+                min_log_luminance: settings.min_log_luminance,
+                log_luminance_range: range,
+                adaptation: 1.0 - (-delta.max(0.0) * settings.speed).exp(),
+                low_percentile: settings.low_percentile,
+                high_percentile: settings.high_percentile.max(settings.low_percentile),
+                reset: self.reset as u32,
+                ..Default::default()
+            }],
+        );
+        self.reset = false;
+    }
+
+    /// Computes the histogram of the image bound in `source`, of size `size`,
+    /// and adapts the exposure to it.
+    pub fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::BindGroup,
+        size: (u32, u32),
+    ) {
+        let bins_size = (HISTOGRAM_BINS * std::mem::size_of::<u32>()) as u64;
+        encoder.clear_buffer(&self.state, 0, Some(bins_size));
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Auto Exposure Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_bind_group(0, source, &[]);
+        pass.set_pipeline(&self.build_pipeline);
+        pass.dispatch_workgroups(
+            size.0.div_ceil(WORKGROUP_SIZE.0),
+            size.1.div_ceil(WORKGROUP_SIZE.1),
+            1,
+        );
+        pass.set_pipeline(&self.average_pipeline);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+}
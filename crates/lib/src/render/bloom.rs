@@ -0,0 +1,336 @@
+use albedo_backend::gpu;
+
+use crate::renderer::Bloom;
+
+const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+/// Maximum number of levels of the downsample chain.
+const MAX_LEVELS: usize = 6;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomUniforms {
+    threshold: f32,
+    knee: f32,
+    radius: f32,
+    padding: u32,
+}
+
+struct Level {
+    size: (u32, u32),
+    down: wgpu::TextureView,
+    up: wgpu::TextureView,
+}
+
+/// Downsample / upsample bloom chain on the linear radiance.
+///
+/// The chain starts at half the render resolution. The result, in
+/// [`Self::output`], is composited by the tonemapper.
+pub(crate) struct BloomPass {
+    prefilter_bgl: wgpu::BindGroupLayout,
+    chain_bgl: wgpu::BindGroupLayout,
+    prefilter_pipeline: wgpu::ComputePipeline,
+    downsample_pipeline: wgpu::ComputePipeline,
+    upsample_pipeline: wgpu::ComputePipeline,
+    uniforms: gpu::Buffer<BloomUniforms>,
+    sampler: wgpu::Sampler,
+    levels: Vec<Level>,
+    downsample_bindgroups: Vec<wgpu::BindGroup>,
+    upsample_bindgroups: Vec<wgpu::BindGroup>,
+    /// Prefilter bind groups of the accumulation render targets.
+    accumulated_bindgroups: Vec<wgpu::BindGroup>,
+}
+
+fn storage_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format: wgpu::TextureFormat::Rgba16Float,
+            view_dimension: wgpu::TextureViewDimension::D2,
+        },
+        count: None,
+    }
+}
+
+fn texture_entry(binding: u32, filterable: bool) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+impl BloomPass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let prefilter_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom Prefilter Layout"),
+            entries: &[
+                texture_entry(0, false),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(2),
+            ],
+        });
+        let chain_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom Chain Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(2),
+                texture_entry(3, false),
+                texture_entry(4, true),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/bloom.wgsl").into()),
+        });
+        let create_pipeline = |bgl: &wgpu::BindGroupLayout, entry_point: &str| {
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Bloom Pipeline Layout"),
+                bind_group_layouts: &[bgl],
+                push_constant_ranges: &[],
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Bloom Pipeline"),
+                layout: Some(&layout),
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let prefilter_pipeline = create_pipeline(&prefilter_bgl, "prefilter");
+        let downsample_pipeline = create_pipeline(&chain_bgl, "downsample");
+        let upsample_pipeline = create_pipeline(&chain_bgl, "upsample");
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            prefilter_bgl,
+            chain_bgl,
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            uniforms: gpu::Buffer::new_uniform(device, 1, None),
+            sampler,
+            levels: Vec::new(),
+            downsample_bindgroups: Vec::new(),
+            upsample_bindgroups: Vec::new(),
+            accumulated_bindgroups: Vec::new(),
+        }
+    }
+
+    /// Re-creates the chain for a render of the given `size`.
+    ///
+    /// Must be called whenever the render targets are re-created.
+    pub fn create_bind_groups(
+        &mut self,
+        device: &wgpu::Device,
+        size: (u32, u32),
+        accumulated: [&wgpu::TextureView; 2],
+    ) {
+        self.levels.clear();
+        let mut level_size = ((size.0 / 2).max(1), (size.1 / 2).max(1));
+        while self.levels.len() < MAX_LEVELS {
+            let create_view = |label: &str| {
+                device
+                    .create_texture(&wgpu::TextureDescriptor {
+                        label: Some(label),
+                        size: wgpu::Extent3d {
+                            width: level_size.0,
+                            height: level_size.1,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: wgpu::TextureFormat::Rgba16Float,
+                        usage: wgpu::TextureUsages::TEXTURE_BINDING
+                            | wgpu::TextureUsages::STORAGE_BINDING,
+                        view_formats: &[],
+                    })
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            };
+            self.levels.push(Level {
+                size: level_size,
+                down: create_view("Bloom Downsample Target"),
+                up: create_view("Bloom Upsample Target"),
+            });
+            if level_size.0.min(level_size.1) <= 4 {
+                break;
+            }
+            level_size = (level_size.0 / 2, level_size.1 / 2);
+        }
+
+        let (chain_bgl, uniforms, sampler) = (&self.chain_bgl, &self.uniforms, &self.sampler);
+        let create_chain_bindgroup =
+            |current: &wgpu::TextureView,
+             previous: &wgpu::TextureView,
+             output: &wgpu::TextureView| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Bloom Chain Bind Group"),
+                    layout: chain_bgl,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: uniforms.inner().as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(output),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: wgpu::BindingResource::TextureView(current),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 4,
+                            resource: wgpu::BindingResource::TextureView(previous),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 5,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                    ],
+                })
+            };
+        let levels = &self.levels;
+        // Level `i + 1` from level `i`.
+        self.downsample_bindgroups = levels
+            .windows(2)
+            .map(|pair| create_chain_bindgroup(&pair[0].down, &pair[0].down, &pair[1].down))
+            .collect();
+        // Level `i` from level `i + 1`, the smallest level is only downsampled.
+        self.upsample_bindgroups = (0..levels.len().saturating_sub(1))
+            .map(|i| {
+                let previous = if i + 2 == levels.len() {
+                    &levels[i + 1].down
+                } else {
+                    &levels[i + 1].up
+                };
+                create_chain_bindgroup(&levels[i].down, previous, &levels[i].up)
+            })
+            .collect();
+
+        self.accumulated_bindgroups = accumulated
+            .iter()
+            .map(|view| self.create_source_bind_group(device, view))
+            .collect();
+    }
+
+    /// Prefilter bind group reading `source`, must be re-created with the chain.
+    pub fn create_source_bind_group(
+        &self,
+        device: &wgpu::Device,
+        source: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Bloom Prefilter Bind Group"),
+            layout: &self.prefilter_bgl,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.uniforms.inner().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&self.levels[0].down),
+                },
+            ],
+        })
+    }
+
+    pub fn accumulated(&self, index: usize) -> &wgpu::BindGroup {
+        &self.accumulated_bindgroups[index]
+    }
+
+    /// Bloom to add to the radiance, at half the render resolution.
+    pub fn output(&self) -> &wgpu::TextureView {
+        match self.levels.as_slice() {
+            [level] => &level.down,
+            levels => &levels[0].up,
+        }
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, settings: &Bloom) {
+        self.uniforms.update(
+            queue,
+            &[BloomUniforms {
+                threshold: settings.threshold,
+                knee: settings.knee.max(0.0),
+                radius: settings.radius.clamp(0.0, 1.0),
+                padding: 0,
+            }],
+        );
+    }
+
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::BindGroup) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Bloom Pass"),
+            timestamp_writes: None,
+        });
+        let dispatch = |pass: &mut wgpu::ComputePass, size: (u32, u32)| {
+            pass.dispatch_workgroups(
+                size.0.div_ceil(WORKGROUP_SIZE.0),
+                size.1.div_ceil(WORKGROUP_SIZE.1),
+                1,
+            );
+        };
+
+        pass.set_pipeline(&self.prefilter_pipeline);
+        pass.set_bind_group(0, source, &[]);
+        dispatch(&mut pass, self.levels[0].size);
+
+        pass.set_pipeline(&self.downsample_pipeline);
+        for (bindgroup, level) in self.downsample_bindgroups.iter().zip(&self.levels[1..]) {
+            pass.set_bind_group(0, bindgroup, &[]);
+            dispatch(&mut pass, level.size);
+        }
+
+        pass.set_pipeline(&self.upsample_pipeline);
+        for (bindgroup, level) in self.upsample_bindgroups.iter().zip(&self.levels).rev() {
+            pass.set_bind_group(0, bindgroup, &[]);
+            dispatch(&mut pass, level.size);
+        }
+    }
+}
@@ -1,3 +1,17 @@
+mod aov;
 mod asvgf;
+mod auto_exposure;
+mod bloom;
+mod ray_generation;
+mod russian_roulette;
+mod tonemap;
+mod upscaler;
 
+pub(crate) use aov::{AovPass, AovSources, AOV_FORMAT};
 pub(crate) use asvgf::ASVGF;
+pub(crate) use auto_exposure::AutoExposurePass;
+pub(crate) use bloom::BloomPass;
+pub(crate) use ray_generation::{CameraUniforms, RayGenerationPass};
+pub(crate) use russian_roulette::RussianRoulettePass;
+pub(crate) use tonemap::{TonemapPass, READBACK_FORMAT};
+pub(crate) use upscaler::{TemporalUpscaler, UpscaleSource};
@@ -0,0 +1,153 @@
+use albedo_backend::gpu;
+use albedo_rtx::uniforms::PerDrawUniforms;
+use albedo_rtx::Ray;
+use glam::Mat4;
+
+use crate::camera::CameraParams;
+
+const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct CameraUniforms {
+    pub view_to_world: [[f32; 4]; 4],
+    pub tan_half_fov: f32,
+    pub aspect_ratio: f32,
+    pub aperture: f32,
+    pub focus_distance: f32,
+    pub blades: u32,
+    pub projection: u32,
+    pub projection_scale: f32,
+    pub fixed_jitter: u32,
+    pub jitter: [f32; 2],
+    pub padding: [u32; 2],
+}
+
+impl CameraUniforms {
+    /// `jitter` replaces the random sub-pixel offset of every pixel, if any.
+    pub fn new(
+        view_to_world: &Mat4,
+        params: &CameraParams,
+        dimensions: (u32, u32),
+        jitter: Option<[f32; 2]>,
+    ) -> Self {
+        Self {
+            view_to_world: view_to_world.to_cols_array_2d(),
+            tan_half_fov: (params.v_fov * 0.5).tan(),
+            aspect_ratio: params.aspect_ratio(dimensions),
+            aperture: params.aperture,
+            focus_distance: params.focus_distance,
+            blades: params.blades,
+            projection: params.projection.id(),
+            projection_scale: params.projection.scale(),
+            fixed_jitter: jitter.is_some() as u32,
+            jitter: jitter.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Generates the primary rays from the camera model.
+///
+/// Replaces the albedo_rtx ray pass, which only supports a fixed pinhole
+/// camera. Rays are jittered over the lens for depth of field.
+pub(crate) struct RayGenerationPass {
+    bgl: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl RayGenerationPass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let uniform_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Ray Generation Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                uniform_entry(1),
+                uniform_entry(2),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ray Generation Pipeline Layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ray Generation Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/ray_generation.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Ray Generation Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Self { bgl, pipeline }
+    }
+
+    pub fn create_frame_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        rays: &gpu::Buffer<Ray>,
+        camera: &gpu::Buffer<CameraUniforms>,
+        global_uniforms: &gpu::Buffer<PerDrawUniforms>,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Ray Generation Bind Group"),
+            layout: &self.bgl,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: rays.inner().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: camera.inner().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: global_uniforms.inner().as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    pub fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bindgroup: &wgpu::BindGroup,
+        size: (u32, u32, u32),
+    ) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Ray Generation Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bindgroup, &[]);
+        pass.dispatch_workgroups(
+            size.0.div_ceil(WORKGROUP_SIZE.0),
+            size.1.div_ceil(WORKGROUP_SIZE.1),
+            size.2,
+        );
+    }
+}
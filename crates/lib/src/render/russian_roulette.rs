@@ -0,0 +1,157 @@
+use albedo_backend::gpu;
+use albedo_rtx::Ray;
+
+use crate::renderer::MAX_BOUNCES;
+
+const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct RouletteUniforms {
+    dimensions: [u32; 2],
+    seed: u32,
+    bounce: u32,
+}
+
+const UNIFORMS_SIZE: u64 = std::mem::size_of::<RouletteUniforms>() as u64;
+
+/// Russian roulette on the ray buffer, run after a shading pass.
+///
+/// Terminated rays keep being intersected, but don't contribute anymore.
+/// Every bounce reads its own uniforms slot, selected with a dynamic offset,
+/// so that each one draws different random numbers.
+pub(crate) struct RussianRoulettePass {
+    bgl: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    uniforms: wgpu::Buffer,
+    /// Distance between two uniforms slots, in bytes.
+    stride: u64,
+}
+
+impl RussianRoulettePass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Russian Roulette Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(UNIFORMS_SIZE),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Russian Roulette Pipeline Layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Russian Roulette Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/russian_roulette.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Russian Roulette Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = UNIFORMS_SIZE.div_ceil(alignment) * alignment;
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Russian Roulette Uniforms"),
+            size: stride * MAX_BOUNCES as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            bgl,
+            pipeline,
+            uniforms,
+            stride,
+        }
+    }
+
+    /// Writes the uniforms of the first `bounces` bounces of the frame.
+    pub fn update(&self, queue: &wgpu::Queue, dimensions: (u32, u32), seed: u32, bounces: u32) {
+        let bounces = bounces.min(MAX_BOUNCES);
+        let mut data = vec![0u8; (self.stride * bounces as u64) as usize];
+        for bounce in 0..bounces {
+            let uniforms = RouletteUniforms {
+                dimensions: [dimensions.0, dimensions.1],
+                seed,
+                bounce,
+            };
+            let offset = (self.stride * bounce as u64) as usize;
+            data[offset..offset + UNIFORMS_SIZE as usize]
+                .copy_from_slice(bytemuck::bytes_of(&uniforms));
+        }
+        if !data.is_empty() {
+            queue.write_buffer(&self.uniforms, 0, &data);
+        }
+    }
+
+    pub fn create_frame_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        rays: &gpu::Buffer<Ray>,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Russian Roulette Bind Group"),
+            layout: &self.bgl,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: rays.inner().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.uniforms,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(UNIFORMS_SIZE),
+                    }),
+                },
+            ],
+        })
+    }
+
+    /// Runs the roulette of `bounce`, whose uniforms were written by
+    /// [`Self::update`].
+    pub fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bindgroup: &wgpu::BindGroup,
+        bounce: u32,
+        size: (u32, u32, u32),
+    ) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Russian Roulette Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        let offset = self.stride * bounce.min(MAX_BOUNCES - 1) as u64;
+        pass.set_bind_group(0, bindgroup, &[offset as u32]);
+        pass.dispatch_workgroups(
+            size.0.div_ceil(WORKGROUP_SIZE.0),
+            size.1.div_ceil(WORKGROUP_SIZE.1),
+            size.2,
+        );
+    }
+}
@@ -0,0 +1,106 @@
+// Decodes an arbitrary output variable into a float target.
+//
+// G-buffer layout, as written by the primary ray pass:
+// - x: octahedral shading normal, packed as two snorm16.
+// - y: instance index, `MISS` when the primary ray hit nothing.
+// - w: distance to the camera, as float bits.
+
+const MISS: u32 = 0xFFFFFFFFu;
+
+const AOV_ALBEDO: u32 = 1u;
+const AOV_SHADING_NORMAL: u32 = 2u;
+const AOV_DEPTH: u32 = 3u;
+const AOV_MOTION: u32 = 4u;
+const AOV_INSTANCE_ID: u32 = 5u;
+const AOV_MATERIAL_ID: u32 = 6u;
+const AOV_VARIANCE: u32 = 7u;
+
+struct Instance {
+    model_to_world: mat4x4<f32>,
+    world_to_model: mat4x4<f32>,
+    material_index: u32,
+    bvh_root_index: u32,
+    vertex_root_index: u32,
+    bvh_primitive_index: u32,
+};
+
+struct Material {
+    color: vec4<f32>,
+    roughness: f32,
+    reflectivity: f32,
+    albedo_texture: u32,
+    mra_texture: u32,
+};
+
+struct Uniforms {
+    aov: u32,
+    padding: vec3<u32>,
+};
+
+@group(0) @binding(0) var gbuffer: texture_2d<u32>;
+@group(0) @binding(1) var motion: texture_2d<f32>;
+@group(0) @binding(2) var moments: texture_2d<f32>;
+@group(0) @binding(3) var<storage, read> instances: array<Instance>;
+@group(0) @binding(4) var<storage, read> materials: array<Material>;
+@group(0) @binding(5) var output: texture_storage_2d<rgba32float, write>;
+@group(0) @binding(6) var<uniform> uniforms: Uniforms;
+
+fn decode_normal(packed: u32) -> vec3<f32> {
+    let e = unpack2x16snorm(packed);
+    var n = vec3<f32>(e, 1.0 - abs(e.x) - abs(e.y));
+    if (n.z < 0.0) {
+        let signs = select(vec2<f32>(-1.0), vec2<f32>(1.0), n.xy >= vec2<f32>(0.0));
+        n = vec3<f32>((1.0 - abs(n.yx)) * signs, n.z);
+    }
+    return normalize(n);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(output);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    let texel = vec2<i32>(id.xy);
+    let data = textureLoad(gbuffer, texel, 0);
+    let hit = data.y != MISS;
+
+    var value = vec4<f32>(0.0);
+    switch uniforms.aov {
+        case AOV_ALBEDO: {
+            if (hit) {
+                // Base color only, textures aren't sampled.
+                value = vec4<f32>(materials[instances[data.y].material_index].color.rgb, 0.0);
+            }
+        }
+        case AOV_SHADING_NORMAL: {
+            if (hit) {
+                value = vec4<f32>(decode_normal(data.x), 0.0);
+            }
+        }
+        case AOV_DEPTH: {
+            value.x = select(-1.0, bitcast<f32>(data.w), hit);
+        }
+        case AOV_MOTION: {
+            // The denoiser stores UV offsets, converted here to pixels.
+            let uv_offset = textureLoad(motion, texel, 0).xy;
+            value = vec4<f32>(uv_offset * vec2<f32>(size), 0.0, 0.0);
+        }
+        case AOV_INSTANCE_ID: {
+            value.x = select(-1.0, f32(data.y), hit);
+        }
+        case AOV_MATERIAL_ID: {
+            if (hit) {
+                value.x = f32(instances[data.y].material_index);
+            } else {
+                value.x = -1.0;
+            }
+        }
+        case AOV_VARIANCE: {
+            let m = textureLoad(moments, texel, 0).xy;
+            value.x = max(m.y - m.x * m.x, 0.0);
+        }
+        default: {}
+    }
+    textureStore(output, texel, value);
+}
@@ -0,0 +1,134 @@
+// Bloom on the linear radiance.
+//
+// `prefilter` thresholds the radiance into the first level of the chain,
+// `downsample` builds the smaller levels, and `upsample` blends them
+// back from the smallest one. The result is added by the tonemapper.
+
+struct Uniforms {
+    // Value of the brightest channel above which pixels bloom.
+    threshold: f32,
+    // Width of the soft transition around the threshold.
+    knee: f32,
+    // Blend factor of the smaller levels, larger values spread further.
+    radius: f32,
+    padding: u32,
+};
+
+// Prefilter.
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> uniforms: Uniforms;
+
+@group(0) @binding(2) var output: texture_storage_2d<rgba16float, write>;
+
+// Downsample and upsample.
+@group(0) @binding(3) var current: texture_2d<f32>;
+@group(0) @binding(4) var previous: texture_2d<f32>;
+@group(0) @binding(5) var linear_sampler: sampler;
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// Bilinear fetch, the source might not be filterable.
+fn load_source(uv: vec2<f32>) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(source));
+    let pos = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(pos));
+    let t = fract(pos);
+    let max_texel = size - 1;
+    let c00 = textureLoad(source, clamp(base, vec2<i32>(0), max_texel), 0).rgb;
+    let c10 = textureLoad(source, clamp(base + vec2<i32>(1, 0), vec2<i32>(0), max_texel), 0).rgb;
+    let c01 = textureLoad(source, clamp(base + vec2<i32>(0, 1), vec2<i32>(0), max_texel), 0).rgb;
+    let c11 = textureLoad(source, clamp(base + vec2<i32>(1, 1), vec2<i32>(0), max_texel), 0).rgb;
+    return mix(mix(c00, c10, t.x), mix(c01, c11, t.x), t.y);
+}
+
+fn threshold(color: vec3<f32>) -> vec3<f32> {
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - uniforms.threshold + uniforms.knee, 0.0, 2.0 * uniforms.knee);
+    soft = soft * soft / (4.0 * uniforms.knee + 1e-5);
+    let contribution = max(soft, brightness - uniforms.threshold) / max(brightness, 1e-5);
+    return color * contribution;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn prefilter(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(output);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    let texel = 1.0 / vec2<f32>(size);
+    let uv = (vec2<f32>(id.xy) + 0.5) * texel;
+
+    // Weighted by the inverse luminance to tame the fireflies of the path tracer.
+    var sum = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0; i < 4; i++) {
+        let offset = vec2<f32>(f32(i & 1), f32(i >> 1u)) - 0.5;
+        let color = max(load_source(uv + offset * 0.5 * texel), vec3<f32>(0.0));
+        let w = 1.0 / (1.0 + luminance(color));
+        sum += color * w;
+        weight += w;
+    }
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(threshold(sum / weight), 1.0));
+}
+
+fn sample_previous(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(previous, linear_sampler, uv, 0.0).rgb;
+}
+
+// 13 taps filter from "Next Generation Post Processing in Call of Duty".
+@compute @workgroup_size(8, 8, 1)
+fn downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(output);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    let uv = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(size);
+    let texel = 1.0 / vec2<f32>(textureDimensions(previous));
+
+    let a = sample_previous(uv + texel * vec2<f32>(-2.0, -2.0));
+    let b = sample_previous(uv + texel * vec2<f32>(0.0, -2.0));
+    let c = sample_previous(uv + texel * vec2<f32>(2.0, -2.0));
+    let d = sample_previous(uv + texel * vec2<f32>(-2.0, 0.0));
+    let e = sample_previous(uv);
+    let f = sample_previous(uv + texel * vec2<f32>(2.0, 0.0));
+    let g = sample_previous(uv + texel * vec2<f32>(-2.0, 2.0));
+    let h = sample_previous(uv + texel * vec2<f32>(0.0, 2.0));
+    let i = sample_previous(uv + texel * vec2<f32>(2.0, 2.0));
+    let j = sample_previous(uv + texel * vec2<f32>(-1.0, -1.0));
+    let k = sample_previous(uv + texel * vec2<f32>(1.0, -1.0));
+    let l = sample_previous(uv + texel * vec2<f32>(-1.0, 1.0));
+    let m = sample_previous(uv + texel * vec2<f32>(1.0, 1.0));
+
+    var color = e * 0.125;
+    color += (a + c + g + i) * 0.03125;
+    color += (b + d + f + h) * 0.0625;
+    color += (j + k + l + m) * 0.125;
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(color, 1.0));
+}
+
+// Blends the tent filtered smaller level with the current one.
+@compute @workgroup_size(8, 8, 1)
+fn upsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(output);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    let uv = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(size);
+    let texel = 1.0 / vec2<f32>(textureDimensions(previous));
+
+    var color = sample_previous(uv) * 4.0;
+    color += (sample_previous(uv + texel * vec2<f32>(0.0, -1.0))
+        + sample_previous(uv + texel * vec2<f32>(-1.0, 0.0))
+        + sample_previous(uv + texel * vec2<f32>(1.0, 0.0))
+        + sample_previous(uv + texel * vec2<f32>(0.0, 1.0))) * 2.0;
+    color += sample_previous(uv + texel * vec2<f32>(-1.0, -1.0))
+        + sample_previous(uv + texel * vec2<f32>(1.0, -1.0))
+        + sample_previous(uv + texel * vec2<f32>(-1.0, 1.0))
+        + sample_previous(uv + texel * vec2<f32>(1.0, 1.0));
+    color /= 16.0;
+
+    let base = textureLoad(current, vec2<i32>(id.xy), 0).rgb;
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(mix(base, color, uniforms.radius), 1.0));
+}
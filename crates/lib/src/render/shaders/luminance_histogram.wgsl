@@ -0,0 +1,110 @@
+// Auto-exposure from a log-luminance histogram.
+//
+// `build` accumulates the histogram of the radiance, and `average` computes
+// the mean log-luminance between two percentiles, adapted over time.
+//
+// Bin 0 holds the pixels darker than the histogram range, they are ignored
+// by the average.
+
+const BIN_COUNT: u32 = 256u;
+// Middle grey the average luminance is mapped to.
+const KEY: f32 = 0.18;
+
+struct Params {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    // Blend factor between the previous and current average.
+    adaptation: f32,
+    low_percentile: f32,
+    high_percentile: f32,
+    // Set to 1 to discard the previous average.
+    reset: u32,
+    padding_0: u32,
+    padding_1: u32,
+};
+
+struct Exposure {
+    bins: array<atomic<u32>, BIN_COUNT>,
+    average_log_luminance: f32,
+    exposure: f32,
+};
+
+@group(0) @binding(0) var radiance: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> params: Params;
+@group(0) @binding(2) var<storage, read_write> state: Exposure;
+
+var<workgroup> local_bins: array<atomic<u32>, BIN_COUNT>;
+var<workgroup> counts: array<u32, BIN_COUNT>;
+
+fn bin_index(color: vec3<f32>) -> u32 {
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    if (luminance < 1e-5) {
+        return 0u;
+    }
+    let t = (log2(luminance) - params.min_log_luminance) / params.log_luminance_range;
+    if (t < 0.0) {
+        return 0u;
+    }
+    return u32(min(t, 1.0) * f32(BIN_COUNT - 2u)) + 1u;
+}
+
+@compute @workgroup_size(16, 16, 1)
+fn build(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32
+) {
+    atomicStore(&local_bins[local_index], 0u);
+    workgroupBarrier();
+
+    let size = textureDimensions(radiance);
+    if (id.x < size.x && id.y < size.y) {
+        let color = textureLoad(radiance, vec2<i32>(id.xy), 0).rgb;
+        atomicAdd(&local_bins[bin_index(color)], 1u);
+    }
+    workgroupBarrier();
+
+    atomicAdd(&state.bins[local_index], atomicLoad(&local_bins[local_index]));
+}
+
+@compute @workgroup_size(256, 1, 1)
+fn average(@builtin(local_invocation_index) local_index: u32) {
+    counts[local_index] = atomicLoad(&state.bins[local_index]);
+    workgroupBarrier();
+    if (local_index != 0u) {
+        return;
+    }
+
+    var total = 0.0;
+    for (var i = 1u; i < BIN_COUNT; i++) {
+        total += f32(counts[i]);
+    }
+    let low = total * params.low_percentile;
+    let high = total * params.high_percentile;
+
+    // Weighted mean of the bins, only counting the pixels between the percentiles.
+    var cumulated = 0.0;
+    var sum = 0.0;
+    var weight = 0.0;
+    for (var i = 1u; i < BIN_COUNT; i++) {
+        let count = f32(counts[i]);
+        let start = max(cumulated, low);
+        let end = min(cumulated + count, high);
+        cumulated += count;
+        if (end <= start) {
+            continue;
+        }
+        let t = (f32(i - 1u) + 0.5) / f32(BIN_COUNT - 2u);
+        sum += (params.min_log_luminance + t * params.log_luminance_range) * (end - start);
+        weight += end - start;
+    }
+
+    var adapted = state.average_log_luminance;
+    if (weight > 0.0) {
+        adapted = sum / weight;
+    }
+    if (params.reset == 0u) {
+        adapted = mix(state.average_log_luminance, adapted, params.adaptation);
+    }
+    state.average_log_luminance = adapted;
+    state.exposure = KEY / exp2(adapted);
+}
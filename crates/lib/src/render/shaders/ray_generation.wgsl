@@ -0,0 +1,152 @@
+// Generates the primary rays, one per pixel.
+//
+// `Ray` mirrors the layout of the albedo_rtx ray payload.
+
+struct Ray {
+    origin: vec4<f32>,
+    dir: vec4<f32>,
+    radiance: vec4<f32>,
+    throughput: vec4<f32>,
+};
+
+struct Camera {
+    view_to_world: mat4x4<f32>,
+    tan_half_fov: f32,
+    aspect_ratio: f32,
+    aperture: f32,
+    focus_distance: f32,
+    blades: u32,
+    // See `Projection::id`.
+    projection: u32,
+    // Orthographic height, fisheye field of view or stereo eye offset.
+    projection_scale: f32,
+    // Set to 1 to use `jitter` for every pixel, for temporal upscaling.
+    fixed_jitter: u32,
+    jitter: vec2<f32>,
+    padding_0: u32,
+    padding_1: u32,
+};
+
+struct Uniforms {
+    dimensions: vec2<u32>,
+    frame_count: u32,
+    seed: u32,
+    bounces: u32,
+    padding_0: u32,
+    padding_1: u32,
+    padding_2: u32,
+};
+
+@group(0) @binding(0) var<storage, read_write> rays: array<Ray>;
+@group(0) @binding(1) var<uniform> camera: Camera;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random(state: ptr<function, u32>) -> f32 {
+    *state = pcg(*state);
+    return f32(*state) / 4294967295.0;
+}
+
+const PI: f32 = 3.14159265359;
+
+const PROJECTION_PERSPECTIVE: u32 = 0u;
+const PROJECTION_ORTHOGRAPHIC: u32 = 1u;
+const PROJECTION_EQUIRECTANGULAR: u32 = 2u;
+const PROJECTION_FISHEYE: u32 = 3u;
+const PROJECTION_OMNI_STEREO: u32 = 4u;
+
+// Uniform sample on the unit disk, or on a regular polygon inscribed in it.
+fn sample_aperture(state: ptr<function, u32>) -> vec2<f32> {
+    let u0 = random(state);
+    let u1 = random(state);
+    if (camera.blades < 3u) {
+        let phi = 2.0 * PI * u1;
+        return sqrt(u0) * vec2<f32>(cos(phi), sin(phi));
+    }
+    // Pick a blade triangle, then a point inside it.
+    let count = f32(camera.blades);
+    let blade = min(floor(random(state) * count), count - 1.0);
+    let a0 = blade * 2.0 * PI / count;
+    let a1 = a0 + 2.0 * PI / count;
+    let s = sqrt(u0);
+    return vec2<f32>(cos(a0), sin(a0)) * (s * (1.0 - u1)) + vec2<f32>(cos(a1), sin(a1)) * (s * u1);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= uniforms.dimensions.x || id.y >= uniforms.dimensions.y) {
+        return;
+    }
+    let index = id.y * uniforms.dimensions.x + id.x;
+    var state = pcg(index ^ pcg(uniforms.seed));
+
+    // Jitter inside the pixel for anti-aliasing.
+    var jitter = vec2<f32>(random(&state), random(&state));
+    if (camera.fixed_jitter != 0u) {
+        jitter = camera.jitter;
+    }
+    let uv = (vec2<f32>(id.xy) + jitter) / vec2<f32>(uniforms.dimensions);
+    let ndc = vec2<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+
+    let right = camera.view_to_world[0].xyz;
+    let up = camera.view_to_world[1].xyz;
+    let forward = camera.view_to_world[2].xyz;
+    let eye = camera.view_to_world[3].xyz;
+
+    var origin = eye;
+    var dir = forward;
+    switch camera.projection {
+        case PROJECTION_ORTHOGRAPHIC: {
+            let half = camera.projection_scale * 0.5;
+            origin = eye + right * (ndc.x * half * camera.aspect_ratio) + up * (ndc.y * half);
+        }
+        case PROJECTION_EQUIRECTANGULAR, PROJECTION_OMNI_STEREO: {
+            let phi = (uv.x - 0.5) * 2.0 * PI;
+            let theta = (0.5 - uv.y) * PI;
+            dir = normalize(
+                forward * (cos(theta) * cos(phi))
+                + right * (cos(theta) * sin(phi))
+                + up * sin(theta)
+            );
+            // Eyes sit on a circle, perpendicular to the horizontal direction.
+            // The offset is zero for the monoscopic projection.
+            origin = eye + (right * cos(phi) - forward * sin(phi)) * camera.projection_scale;
+        }
+        case PROJECTION_FISHEYE: {
+            let p = vec2<f32>(ndc.x * camera.aspect_ratio, ndc.y);
+            let r = length(p);
+            if (r > 0.0) {
+                let theta = min(r * camera.projection_scale * 0.5, PI);
+                let side = (right * p.x + up * p.y) / r;
+                dir = normalize(forward * cos(theta) + side * sin(theta));
+            }
+        }
+        default: {
+            dir = normalize(
+                forward
+                + right * (ndc.x * camera.tan_half_fov * camera.aspect_ratio)
+                + up * (ndc.y * camera.tan_half_fov)
+            );
+        }
+    }
+
+    if (camera.projection == PROJECTION_PERSPECTIVE && camera.aperture > 0.0) {
+        // Thin lens: every ray of the pixel converges on the focus plane.
+        let focus = eye + dir * (camera.focus_distance / dot(dir, forward));
+        let lens = sample_aperture(&state) * camera.aperture;
+        origin = eye + right * lens.x + up * lens.y;
+        dir = normalize(focus - origin);
+    }
+
+    var ray: Ray;
+    ray.origin = vec4<f32>(origin, 0.0);
+    ray.dir = vec4<f32>(dir, 0.0);
+    ray.radiance = vec4<f32>(0.0);
+    ray.throughput = vec4<f32>(1.0);
+    rays[index] = ray;
+}
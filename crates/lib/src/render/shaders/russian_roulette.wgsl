@@ -0,0 +1,48 @@
+// Randomly terminates paths with a low throughput, and boosts the surviving
+// ones to keep the estimator unbiased.
+//
+// `Ray` mirrors the layout of the albedo_rtx ray payload.
+
+struct Ray {
+    origin: vec4<f32>,
+    dir: vec4<f32>,
+    radiance: vec4<f32>,
+    throughput: vec4<f32>,
+};
+
+// One slot per bounce, selected with a dynamic offset.
+struct Uniforms {
+    dimensions: vec2<u32>,
+    seed: u32,
+    bounce: u32,
+};
+
+@group(0) @binding(0) var<storage, read_write> rays: array<Ray>;
+@group(0) @binding(1) var<uniform> uniforms: Uniforms;
+
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+const MIN_SURVIVAL: f32 = 0.05;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= uniforms.dimensions.x || id.y >= uniforms.dimensions.y) {
+        return;
+    }
+    let index = id.y * uniforms.dimensions.x + id.x;
+    let throughput = rays[index].throughput;
+    let survival = clamp(max(throughput.x, max(throughput.y, throughput.z)), MIN_SURVIVAL, 1.0);
+
+    let state = pcg(index ^ pcg(uniforms.seed ^ pcg(uniforms.bounce + 0x9e3779b9u)));
+    let u = f32(state) / 4294967295.0;
+    if (u >= survival) {
+        // Terminated paths don't gather any more radiance.
+        rays[index].throughput = vec4<f32>(0.0, 0.0, 0.0, throughput.w);
+    } else {
+        rays[index].throughput = vec4<f32>(throughput.xyz / survival, throughput.w);
+    }
+}
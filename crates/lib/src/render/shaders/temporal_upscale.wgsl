@@ -0,0 +1,97 @@
+// Reconstructs a full resolution image from jittered low resolution frames.
+//
+// Every frame, the low resolution samples are splatted on the output pixels
+// around their jittered position, and blended with the reprojected history.
+//
+// Motion vectors and G-buffers are the ones of the ASVGF pass:
+// * motion vectors are the UV offset from the previous to the current frame.
+// * the G-buffer `w` channel stores the hit distance as float bits.
+
+struct Uniforms {
+    // Sub-pixel offset of the low resolution samples, in [0, 1).
+    jitter: vec2<f32>,
+    // Set to 1 to discard the history.
+    reset: u32,
+    padding: u32,
+};
+
+@group(0) @binding(0) var color: texture_2d<f32>;
+@group(0) @binding(1) var motion: texture_2d<f32>;
+@group(0) @binding(2) var gbuffer_current: texture_2d<u32>;
+@group(0) @binding(3) var gbuffer_previous: texture_2d<u32>;
+@group(0) @binding(4) var history: texture_2d<f32>;
+@group(0) @binding(5) var history_sampler: sampler;
+@group(0) @binding(6) var output: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(7) var<uniform> uniforms: Uniforms;
+
+// Maximum accumulated weight, bounds the latency of the history.
+const MAX_WEIGHT: f32 = 16.0;
+// Relative hit distance difference above which the history is rejected.
+const DEPTH_TOLERANCE: f32 = 0.1;
+
+fn depth(gbuffer: vec4<u32>) -> f32 {
+    return bitcast<f32>(gbuffer.w);
+}
+
+fn load_color(texel: vec2<i32>, size: vec2<i32>) -> vec3<f32> {
+    return textureLoad(color, clamp(texel, vec2<i32>(0), size - 1), 0).rgb;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_size = textureDimensions(output);
+    if (id.x >= out_size.x || id.y >= out_size.y) {
+        return;
+    }
+    let in_size = vec2<i32>(textureDimensions(color));
+    let scale = vec2<f32>(in_size) / vec2<f32>(out_size);
+    let uv = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(out_size);
+
+    // Closest low resolution sample, and its distance in output pixels.
+    let in_pos = uv * vec2<f32>(in_size) - uniforms.jitter;
+    let texel = clamp(vec2<i32>(floor(in_pos + 0.5)), vec2<i32>(0), in_size - 1);
+    let sample_pos = (vec2<f32>(texel) + uniforms.jitter) / scale;
+    let offset = vec2<f32>(id.xy) + 0.5 - sample_pos;
+    let sample_weight = exp(-2.0 * dot(offset, offset) * min(scale.x, scale.y));
+
+    // Neighborhood bounds, used to clamp the history.
+    let current = load_color(texel, in_size);
+    var color_min = current;
+    var color_max = current;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let c = load_color(texel + vec2<i32>(x, y), in_size);
+            color_min = min(color_min, c);
+            color_max = max(color_max, c);
+        }
+    }
+
+    // Reproject the history, and reject disoccluded pixels.
+    let previous_uv = uv - textureLoad(motion, texel, 0).xy;
+    var valid = uniforms.reset == 0u
+        && all(previous_uv >= vec2<f32>(0.0))
+        && all(previous_uv <= vec2<f32>(1.0));
+    if (valid) {
+        let previous_texel = clamp(
+            vec2<i32>(previous_uv * vec2<f32>(in_size)),
+            vec2<i32>(0),
+            in_size - 1
+        );
+        let current_depth = depth(textureLoad(gbuffer_current, texel, 0));
+        let previous_depth = depth(textureLoad(gbuffer_previous, previous_texel, 0));
+        valid = abs(current_depth - previous_depth)
+            <= DEPTH_TOLERANCE * max(abs(current_depth), 1e-4);
+    }
+
+    var accumulated = vec4<f32>(current, 1.0);
+    if (valid) {
+        let previous = textureSampleLevel(history, history_sampler, previous_uv, 0.0);
+        let clamped = clamp(previous.rgb, color_min, color_max);
+        let weight = min(previous.a, MAX_WEIGHT);
+        accumulated = vec4<f32>(
+            (clamped * weight + current * sample_weight) / max(weight + sample_weight, 1e-4),
+            weight + sample_weight
+        );
+    }
+    textureStore(output, vec2<i32>(id.xy), accumulated);
+}
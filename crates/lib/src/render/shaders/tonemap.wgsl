@@ -0,0 +1,196 @@
+// Maps the linear radiance to the display range.
+//
+// The transfer function is applied in the shader, and undone when writing to
+// an sRGB target, so that sRGB and linear targets give identical values.
+
+struct Uniforms {
+    // Linear scale, `2^EV`.
+    exposure: f32,
+    // See `ToneMapping`.
+    tone_mapping: u32,
+    // Display gamma, `0` uses the sRGB curve.
+    gamma: f32,
+    // Set to 1 to scale the exposure by the adapted one.
+    auto_exposure: u32,
+    // Input range of the grading LUT.
+    lut_domain_min: vec3<f32>,
+    // Set to 1 to apply the grading LUT.
+    lut_enabled: u32,
+    lut_domain_max: vec3<f32>,
+    // Scale of the bloom added to the radiance, `0` when disabled.
+    bloom_intensity: f32,
+};
+
+// Written by the luminance histogram pass.
+struct ExposureState {
+    bins: array<u32, 256>,
+    average_log_luminance: f32,
+    exposure: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var radiance: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> uniforms: Uniforms;
+@group(0) @binding(2) var<storage, read> exposure_state: ExposureState;
+@group(1) @binding(0) var lut: texture_3d<f32>;
+@group(1) @binding(1) var bloom: texture_2d<f32>;
+@group(1) @binding(2) var linear_sampler: sampler;
+
+// Set when the render target encodes to sRGB on write.
+override TARGET_SRGB: bool = false;
+
+const OPERATOR_CLAMP: u32 = 0u;
+const OPERATOR_REINHARD: u32 = 1u;
+const OPERATOR_ACES: u32 = 2u;
+const OPERATOR_AGX: u32 = 3u;
+
+@vertex
+fn main_vs(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // Fullscreen triangle.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// Bilinear filtering, float32 textures aren't filterable on every device.
+fn sample_radiance(uv: vec2<f32>) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(radiance));
+    let pos = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(pos));
+    let t = fract(pos);
+    let max_texel = size - 1;
+    let c00 = textureLoad(radiance, clamp(base, vec2<i32>(0), max_texel), 0).rgb;
+    let c10 = textureLoad(radiance, clamp(base + vec2<i32>(1, 0), vec2<i32>(0), max_texel), 0).rgb;
+    let c01 = textureLoad(radiance, clamp(base + vec2<i32>(0, 1), vec2<i32>(0), max_texel), 0).rgb;
+    let c11 = textureLoad(radiance, clamp(base + vec2<i32>(1, 1), vec2<i32>(0), max_texel), 0).rgb;
+    return mix(mix(c00, c10, t.x), mix(c01, c11, t.x), t.y);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve.
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    return (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+}
+
+fn agx_contrast(x: vec3<f32>) -> vec3<f32> {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    return 15.5 * x4 * x2
+        - 40.14 * x4 * x
+        + 31.96 * x4
+        - 6.868 * x2 * x
+        + 0.4298 * x2
+        + 0.1191 * x
+        - 0.00232;
+}
+
+// Minimal AgX, with the default look.
+fn agx(color: vec3<f32>) -> vec3<f32> {
+    let inset = mat3x3<f32>(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104
+    );
+    let outset = mat3x3<f32>(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116
+    );
+    let min_ev = -12.47393;
+    let max_ev = 4.026069;
+
+    var v = inset * color;
+    v = clamp(log2(max(v, vec3<f32>(1e-10))), vec3<f32>(min_ev), vec3<f32>(max_ev));
+    v = (v - min_ev) / (max_ev - min_ev);
+    v = outset * agx_contrast(v);
+    // The curve outputs display values, go back to linear.
+    return pow(max(v, vec3<f32>(0.0)), vec3<f32>(2.2));
+}
+
+fn tonemap(x: vec3<f32>) -> vec3<f32> {
+    switch (uniforms.tone_mapping) {
+        case OPERATOR_REINHARD: {
+            return x / (1.0 + x);
+        }
+        case OPERATOR_ACES: {
+            return aces(x);
+        }
+        case OPERATOR_AGX: {
+            return agx(x);
+        }
+        default: {
+            return x;
+        }
+    }
+}
+
+// Trilinear lookup in the grading LUT.
+fn grade(color: vec3<f32>) -> vec3<f32> {
+    let range = uniforms.lut_domain_max - uniforms.lut_domain_min;
+    let t = clamp((color - uniforms.lut_domain_min) / range, vec3<f32>(0.0), vec3<f32>(1.0));
+    let size = vec3<i32>(textureDimensions(lut));
+    let pos = t * vec3<f32>(size - 1);
+    let base = min(vec3<i32>(floor(pos)), size - 2);
+    let f = pos - vec3<f32>(base);
+
+    let c000 = textureLoad(lut, base, 0).rgb;
+    let c100 = textureLoad(lut, base + vec3<i32>(1, 0, 0), 0).rgb;
+    let c010 = textureLoad(lut, base + vec3<i32>(0, 1, 0), 0).rgb;
+    let c110 = textureLoad(lut, base + vec3<i32>(1, 1, 0), 0).rgb;
+    let c001 = textureLoad(lut, base + vec3<i32>(0, 0, 1), 0).rgb;
+    let c101 = textureLoad(lut, base + vec3<i32>(1, 0, 1), 0).rgb;
+    let c011 = textureLoad(lut, base + vec3<i32>(0, 1, 1), 0).rgb;
+    let c111 = textureLoad(lut, base + vec3<i32>(1, 1, 1), 0).rgb;
+    let c00 = mix(c000, c100, f.x);
+    let c10 = mix(c010, c110, f.x);
+    let c01 = mix(c001, c101, f.x);
+    let c11 = mix(c011, c111, f.x);
+    return mix(mix(c00, c10, f.y), mix(c01, c11, f.y), f.z);
+}
+
+fn linear_to_srgb(x: vec3<f32>) -> vec3<f32> {
+    let low = x * 12.92;
+    let high = 1.055 * pow(x, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, x <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(x: vec3<f32>) -> vec3<f32> {
+    let low = x / 12.92;
+    let high = pow((x + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, x <= vec3<f32>(0.04045));
+}
+
+@fragment
+fn main_fs(in: VertexOutput) -> @location(0) vec4<f32> {
+    var exposure = uniforms.exposure;
+    if (uniforms.auto_exposure != 0u) {
+        exposure *= exposure_state.exposure;
+    }
+    var radiance = sample_radiance(in.uv);
+    if (uniforms.bloom_intensity > 0.0) {
+        radiance += textureSampleLevel(bloom, linear_sampler, in.uv, 0.0).rgb * uniforms.bloom_intensity;
+    }
+    let color = max(radiance * exposure, vec3<f32>(0.0));
+    let mapped = clamp(tonemap(color), vec3<f32>(0.0), vec3<f32>(1.0));
+
+    var encoded: vec3<f32>;
+    if (uniforms.gamma > 0.0) {
+        encoded = pow(mapped, vec3<f32>(1.0 / uniforms.gamma));
+    } else {
+        encoded = linear_to_srgb(mapped);
+    }
+    // LUTs are authored for display encoded values.
+    if (uniforms.lut_enabled != 0u) {
+        encoded = clamp(grade(encoded), vec3<f32>(0.0), vec3<f32>(1.0));
+    }
+    if (TARGET_SRGB) {
+        // Undone by the hardware on write.
+        encoded = srgb_to_linear(encoded);
+    }
+    return vec4<f32>(encoded, 1.0);
+}
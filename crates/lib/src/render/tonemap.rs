@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use crate::lut::Lut;
+use crate::renderer::{ToneMapping, TonemapSettings};
+
+/// Format of the render target used to read pixels back.
+pub(crate) const READBACK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct TonemapUniforms {
+    exposure: f32,
+    tone_mapping: u32,
+    gamma: f32,
+    auto_exposure: u32,
+    lut_domain_min: [f32; 3],
+    lut_enabled: u32,
+    lut_domain_max: [f32; 3],
+    bloom_intensity: f32,
+}
+
+impl TonemapUniforms {
+    fn new(settings: &TonemapSettings, lut_domain: Option<([f32; 3], [f32; 3])>) -> Self {
+        let (lut_domain_min, lut_domain_max) = lut_domain.unwrap_or(([0.0; 3], [1.0; 3]));
+        Self {
+            exposure: settings.exposure.exp2(),
+            tone_mapping: match settings.tone_mapping {
+                ToneMapping::Clamp => 0,
+                ToneMapping::Reinhard => 1,
+                ToneMapping::AcesFilmic => 2,
+                ToneMapping::AgX => 3,
+            },
+            gamma: settings.gamma.unwrap_or(0.0),
+            auto_exposure: settings.auto_exposure.is_some() as u32,
+            lut_domain_min,
+            lut_enabled: lut_domain.is_some() as u32,
+            lut_domain_max,
+            bloom_intensity: settings.bloom.map_or(0.0, |bloom| bloom.intensity),
+        }
+    }
+}
+
+/// Exposure, tone curve and display encoding of a radiance texture.
+///
+/// Draws a fullscreen triangle, either to the swapchain or to a
+/// [`READBACK_FORMAT`] target. Both give the same values.
+pub(crate) struct TonemapPass {
+    bgl: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    readback_pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    /// Output of the [`super::AutoExposurePass`].
+    exposure: wgpu::Buffer,
+    /// Grading LUT and bloom.
+    effects_bgl: wgpu::BindGroupLayout,
+    effects_bindgroup: wgpu::BindGroup,
+    lut: wgpu::TextureView,
+    bloom: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    settings: TonemapSettings,
+    /// Domain of the grading LUT, if any.
+    lut_domain: Option<([f32; 3], [f32; 3])>,
+}
+
+impl TonemapPass {
+    pub fn new(
+        device: &wgpu::Device,
+        swapchain_format: wgpu::TextureFormat,
+        exposure: &wgpu::Buffer,
+    ) -> Self {
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tonemap Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let effects_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tonemap Effects Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D3,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&bgl, &effects_bgl],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tonemap Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/tonemap.wgsl").into()),
+        });
+        let pipeline = Self::create_pipeline(device, &pipeline_layout, &module, swapchain_format);
+        let readback_pipeline =
+            Self::create_pipeline(device, &pipeline_layout, &module, READBACK_FORMAT);
+
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tonemap Uniforms"),
+            contents: bytemuck::bytes_of(&TonemapUniforms::new(&TonemapSettings::default(), None)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        // Placeholders, never read while grading and bloom are disabled.
+        let lut = Self::create_lut(device, 2, None);
+        let bloom = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Bloom Placeholder"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba16Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Tonemap Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let effects_bindgroup =
+            Self::create_effects_bindgroup(device, &effects_bgl, &lut, &bloom, &sampler);
+        Self {
+            bgl,
+            pipeline,
+            readback_pipeline,
+            uniforms,
+            exposure: exposure.clone(),
+            effects_bgl,
+            effects_bindgroup,
+            lut,
+            bloom,
+            sampler,
+            settings: TonemapSettings::default(),
+            lut_domain: None,
+        }
+    }
+
+    fn create_effects_bindgroup(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        lut: &wgpu::TextureView,
+        bloom: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Tonemap Effects Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(lut),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(bloom),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_lut(
+        device: &wgpu::Device,
+        size: u32,
+        data: Option<(&wgpu::Queue, &[[f32; 4]])>,
+    ) -> wgpu::TextureView {
+        let descriptor = wgpu::TextureDescriptor {
+            label: Some("Grading LUT"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: size,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        };
+        let texture = match data {
+            Some((queue, data)) => device.create_texture_with_data(
+                queue,
+                &descriptor,
+                wgpu::util::TextureDataOrder::LayerMajor,
+                bytemuck::cast_slice(data),
+            ),
+            None => device.create_texture(&descriptor),
+        };
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        module: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let constants = HashMap::from([(
+            "TARGET_SRGB".to_string(),
+            if format.is_srgb() { 1.0 } else { 0.0 },
+        )]);
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tonemap Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: Some("main_vs"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: Some("main_fs"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, settings: &TonemapSettings) {
+        self.settings = *settings;
+        self.write_uniforms(queue);
+    }
+
+    /// Grades the tonemapped image with `lut`, or disables grading.
+    pub fn set_lut(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, lut: Option<&Lut>) {
+        self.lut_domain = lut.map(|lut| (lut.domain_min, lut.domain_max));
+        if let Some(lut) = lut {
+            let (size, data) = lut.texture_data();
+            self.lut = Self::create_lut(device, size, Some((queue, &data)));
+            self.effects_bindgroup = Self::create_effects_bindgroup(
+                device,
+                &self.effects_bgl,
+                &self.lut,
+                &self.bloom,
+                &self.sampler,
+            );
+        }
+        self.write_uniforms(queue);
+    }
+
+    /// Must be called whenever the bloom chain is re-created.
+    pub fn set_bloom_texture(&mut self, device: &wgpu::Device, bloom: &wgpu::TextureView) {
+        self.bloom = bloom.clone();
+        self.effects_bindgroup = Self::create_effects_bindgroup(
+            device,
+            &self.effects_bgl,
+            &self.lut,
+            &self.bloom,
+            &self.sampler,
+        );
+    }
+
+    fn write_uniforms(&self, queue: &wgpu::Queue) {
+        let uniforms = TonemapUniforms::new(&self.settings, self.lut_domain);
+        queue.write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    pub fn create_frame_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        radiance: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Tonemap Bind Group"),
+            layout: &self.bgl,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(radiance),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.exposure.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Draws to `view`, which must use the swapchain format, or
+    /// [`READBACK_FORMAT`] if `readback` is set.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        bindgroup: &wgpu::BindGroup,
+        readback: bool,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tonemap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let pipeline = if readback {
+            &self.readback_pipeline
+        } else {
+            &self.pipeline
+        };
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, bindgroup, &[]);
+        pass.set_bind_group(1, &self.effects_bindgroup, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,270 @@
+use albedo_backend::gpu;
+
+use crate::render::ASVGF;
+use crate::Device;
+
+const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+/// Length of the jitter sequence.
+const JITTER_SAMPLES: u32 = 16;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct UpscaleUniforms {
+    jitter: [f32; 2],
+    reset: u32,
+    padding: u32,
+}
+
+/// Low resolution image reconstructed by the [`TemporalUpscaler`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum UpscaleSource {
+    /// Output of the A-Trous filter, in the main render target.
+    Denoised = 0,
+    /// Temporally accumulated radiance of the ASVGF pass.
+    Temporal = 1,
+}
+
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut f = 1.0;
+    while index > 0 {
+        f /= base as f32;
+        result += f * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Reconstructs a full resolution image from the downsampled render.
+///
+/// Primary rays are shifted by the same sub-pixel [`Self::jitter`] for every
+/// pixel, and the frames are accumulated at full resolution using the ASVGF
+/// motion vectors and G-buffers.
+pub(crate) struct TemporalUpscaler {
+    bgl: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    outputs: [wgpu::TextureView; 2],
+    uniforms: gpu::Buffer<UpscaleUniforms>,
+    /// Indexed by source, ASVGF frame, and output.
+    bindgroups: Vec<wgpu::BindGroup>,
+    size: (u32, u32),
+    jitter_index: u32,
+    reset: bool,
+}
+
+impl TemporalUpscaler {
+    pub fn new(device: &Device, size: (u32, u32)) -> Self {
+        let texture_entry =
+            |binding: u32, sample_type: wgpu::TextureSampleType| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            };
+        let unfilterable = wgpu::TextureSampleType::Float { filterable: false };
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Temporal Upscale Layout"),
+            entries: &[
+                texture_entry(0, unfilterable),
+                texture_entry(1, unfilterable),
+                texture_entry(2, wgpu::TextureSampleType::Uint),
+                texture_entry(3, wgpu::TextureSampleType::Uint),
+                texture_entry(4, wgpu::TextureSampleType::Float { filterable: true }),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: wgpu::TextureFormat::Rgba16Float,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 7,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Temporal Upscale Pipeline Layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Temporal Upscale Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/temporal_upscale.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Temporal Upscale Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let outputs = [0, 1].map(|i| {
+            let label = format!("Upscaled Render Target {}", i);
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(&label),
+                    size: wgpu::Extent3d {
+                        width: size.0,
+                        height: size.1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba16Float,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::STORAGE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+
+        Self {
+            bgl,
+            pipeline,
+            outputs,
+            uniforms: gpu::Buffer::new_uniform(device, 1, None),
+            bindgroups: Vec::new(),
+            size,
+            jitter_index: 0,
+            reset: true,
+        }
+    }
+
+    /// Must be called whenever the ASVGF resources or the main render target
+    /// are re-created.
+    pub fn create_bind_groups(&mut self, device: &Device, asvgf: &ASVGF, main: &wgpu::TextureView) {
+        let pingpong = &asvgf.resources.pingpong;
+        let mut bindgroups = Vec::with_capacity(8);
+        for source in [UpscaleSource::Denoised, UpscaleSource::Temporal] {
+            for frame in 0..2 {
+                let color = match source {
+                    UpscaleSource::Denoised => main,
+                    UpscaleSource::Temporal => &pingpong[frame].radiance,
+                };
+                for output in 0..2 {
+                    bindgroups.push(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("Temporal Upscale Bind Group"),
+                        layout: &self.bgl,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(color),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &asvgf.resources.motion,
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &pingpong[frame].gbuffer,
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &pingpong[1 - frame].gbuffer,
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 4,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &self.outputs[1 - output],
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 5,
+                                resource: wgpu::BindingResource::Sampler(device.sampler_linear()),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 6,
+                                resource: wgpu::BindingResource::TextureView(&self.outputs[output]),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 7,
+                                resource: self.uniforms.inner().as_entire_binding(),
+                            },
+                        ],
+                    }));
+                }
+            }
+        }
+        self.bindgroups = bindgroups;
+        self.reset = true;
+    }
+
+    /// Sub-pixel offset of the primary rays for the next frame.
+    pub fn jitter(&self) -> [f32; 2] {
+        let index = self.jitter_index % JITTER_SAMPLES + 1;
+        [halton(index, 2), halton(index, 3)]
+    }
+
+    /// Discards the history on the next frame.
+    pub fn reset(&mut self) {
+        self.reset = true;
+    }
+
+    pub fn output(&self, index: usize) -> &wgpu::TextureView {
+        &self.outputs[index]
+    }
+
+    /// Reconstructs the frame into the output at `output`, reading the
+    /// history from the other one.
+    pub fn dispatch(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        source: UpscaleSource,
+        asvgf_frame: usize,
+        output: usize,
+    ) {
+        self.uniforms.update(
+            queue,
+            &[UpscaleUniforms {
+                jitter: self.jitter(),
+                reset: self.reset as u32,
+                ..Default::default()
+            }],
+        );
+        self.reset = false;
+        self.jitter_index = self.jitter_index.wrapping_add(1);
+
+        let index = (source as usize * 2 + asvgf_frame) * 2 + output;
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Temporal Upscale Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bindgroups[index], &[]);
+        pass.dispatch_workgroups(
+            self.size.0.div_ceil(WORKGROUP_SIZE.0),
+            self.size.1.div_ceil(WORKGROUP_SIZE.1),
+            1,
+        );
+    }
+}
@@ -9,10 +9,16 @@ use albedo_rtx::{passes, DenoiseResources, RadianceParameters, RaytraceResources
 use glam::Mat4;
 use wgpu::naga::FastHashMap;
 
+use crate::camera::CameraParams;
 use crate::device::Device;
 use crate::errors::Error;
-use crate::render::ASVGF;
-use crate::scene::SceneGPU;
+use crate::lut::Lut;
+use crate::render::{
+    AovPass, AovSources, AutoExposurePass, BloomPass, CameraUniforms, RayGenerationPass,
+    RussianRoulettePass, TemporalUpscaler, TonemapPass, UpscaleSource, AOV_FORMAT, ASVGF,
+    READBACK_FORMAT,
+};
+use crate::scene::{RayHit, Scene, SceneGPU};
 use crate::ProbeGPU;
 
 fn get_downsampled_size(size: &(u32, u32), factor: f32) -> (u32, u32) {
@@ -25,6 +31,7 @@ struct RenderTargets {
     main: wgpu::TextureView,
     main_texture: wgpu::Texture,
     second: wgpu::TextureView,
+    second_texture: wgpu::Texture,
 }
 
 impl RenderTargets {
@@ -56,26 +63,30 @@ impl RenderTargets {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba32Float,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
         Self {
             main: main_texture.create_view(&wgpu::TextureViewDescriptor::default()),
             main_texture,
             second: render_target2.create_view(&wgpu::TextureViewDescriptor::default()),
+            second_texture: render_target2,
         }
     }
 }
 
 struct BindGroups {
     generate_ray_pass: wgpu::BindGroup,
+    russian_roulette_pass: wgpu::BindGroup,
     intersection_pass: wgpu::BindGroup,
     shading_pass: wgpu::BindGroup,
     primary_rays: [wgpu::BindGroup; 2],
     accumulate_pass: wgpu::BindGroup,
     accumulate_pass2: wgpu::BindGroup,
-    blit_pass: wgpu::BindGroup,
-    blit_pass2: wgpu::BindGroup,
+    tonemap_pass: wgpu::BindGroup,
+    tonemap_pass2: wgpu::BindGroup,
 }
 
 impl BindGroups {
@@ -84,22 +95,19 @@ impl BindGroups {
         render_targets: &RenderTargets,
         resources: &RaytraceResources,
         denoise_res: &DenoiseResources,
-        ray_pass_desc: &passes::RayPass,
+        generate_ray_pass: wgpu::BindGroup,
+        russian_roulette_pass: wgpu::BindGroup,
         intersector_pass_desc: &passes::IntersectorPass,
         shading_pass_desc: &passes::ShadingPass,
         primary_rays_pass_desc: &passes::PrimaryRayPass,
         accumulation_pass_desc: &passes::AccumulationPass,
-        blit_pass: &passes::BlitPass,
+        tonemap_pass: &TonemapPass,
     ) -> Self {
         let denoise_pong = denoise_res.pong();
 
         BindGroups {
-            generate_ray_pass: ray_pass_desc.create_frame_bind_groups(
-                device,
-                resources.rays,
-                resources.camera_uniforms,
-                resources.global_uniforms,
-            ),
+            generate_ray_pass,
+            russian_roulette_pass,
             intersection_pass: intersector_pass_desc.create_frame_bind_groups(
                 device,
                 resources.intersections,
@@ -130,33 +138,183 @@ impl BindGroups {
                 &render_targets.main,
                 &device.sampler_nearest(),
             ),
-            blit_pass: blit_pass.create_frame_bind_groups(
-                device,
-                &render_targets.main,
-                &device.sampler_nearest(),
-                resources.global_uniforms,
-            ),
-            blit_pass2: blit_pass.create_frame_bind_groups(
-                device,
-                &render_targets.second,
-                &device.sampler_nearest(),
-                resources.global_uniforms,
-            ),
+            tonemap_pass: tonemap_pass.create_frame_bind_groups(device, &render_targets.main),
+            tonemap_pass2: tonemap_pass.create_frame_bind_groups(device, &render_targets.second),
         }
     }
 }
 
 pub struct Passes {
-    pub rays: passes::RayPass,
     pub intersection: passes::IntersectorPass,
     pub shading: passes::ShadingPass,
     pub primary_rays: passes::PrimaryRayPass,
     pub accumulation: passes::AccumulationPass,
-    pub blit: passes::BlitPass,
     pub lightmap: passes::LightmapPass,
     pub blit_texture: passes::BlitTexturePass,
 }
 
+/// Paths are cut after this many bounces, whatever the [`BounceSettings`].
+pub const MAX_BOUNCES: u32 = 32;
+
+/// Path length used by [`Renderer::raytrace`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BounceSettings {
+    /// Maximum number of bounces while the accumulation restarts every frame.
+    pub moving: u32,
+    /// Maximum number of bounces while accumulating.
+    pub converging: u32,
+    /// Paths are randomly terminated based on their throughput after this
+    /// many bounces. `None` disables Russian roulette.
+    pub russian_roulette: Option<u32>,
+}
+
+impl Default for BounceSettings {
+    fn default() -> Self {
+        Self {
+            moving: 3,
+            converging: 8,
+            russian_roulette: Some(3),
+        }
+    }
+}
+
+/// Tone curve applied by [`Renderer::blit`] and [`Renderer::read_pixels`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ToneMapping {
+    /// Clips values above one.
+    #[default]
+    Clamp,
+    Reinhard,
+    AcesFilmic,
+    AgX,
+}
+
+/// Exposure adapted to the average luminance of the radiance.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AutoExposure {
+    /// Log2 luminance range covered by the histogram.
+    pub min_log_luminance: f32,
+    pub max_log_luminance: f32,
+    /// Adaptation rate, higher values reach the target exposure faster.
+    pub speed: f32,
+    /// Fraction of the darkest pixels ignored by the average.
+    pub low_percentile: f32,
+    /// Fraction of the pixels, from the darkest, used by the average.
+    pub high_percentile: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            min_log_luminance: -10.0,
+            max_log_luminance: 6.0,
+            speed: 1.5,
+            low_percentile: 0.5,
+            high_percentile: 0.95,
+        }
+    }
+}
+
+/// Glow around the brightest parts of the radiance, added before exposure.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bloom {
+    /// Value of the brightest channel above which pixels bloom.
+    pub threshold: f32,
+    /// Width of the soft transition around the threshold.
+    pub knee: f32,
+    /// Amount of bloom added to the radiance.
+    pub intensity: f32,
+    /// Spread of the glow, in [0, 1].
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.05,
+            radius: 0.7,
+        }
+    }
+}
+
+/// Maps the accumulated radiance to display values.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TonemapSettings {
+    /// Exposure, in stops. Used as a compensation with auto-exposure.
+    pub exposure: f32,
+    pub auto_exposure: Option<AutoExposure>,
+    pub bloom: Option<Bloom>,
+    pub tone_mapping: ToneMapping,
+    /// Display gamma. `None` uses the sRGB transfer function.
+    pub gamma: Option<f32>,
+}
+
+/// Log-luminance histogram built by the auto-exposure.
+#[derive(Clone, Debug)]
+pub struct LuminanceHistogram {
+    /// The first bin counts the pixels darker than `min_log_luminance`, the
+    /// others evenly split the histogram range.
+    pub bins: Vec<u32>,
+    pub min_log_luminance: f32,
+    pub max_log_luminance: f32,
+    /// Adapted average, in log2 luminance.
+    pub average_log_luminance: f32,
+    /// Linear exposure scale applied before tone mapping.
+    pub exposure: f32,
+}
+
+/// Arbitrary output variable, read back with [`Renderer::read_aov`].
+///
+/// Apart from [`Aov::Beauty`], the values are those of the last primary hit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Aov {
+    /// Accumulated linear radiance.
+    Beauty,
+    /// Base color of the material, without textures.
+    Albedo,
+    /// World space shading normal.
+    ShadingNormal,
+    /// Distance to the camera, `-1` on a miss.
+    Depth,
+    /// Screen space motion since the previous frame, in pixels.
+    ///
+    /// Current minus previous position, with y pointing down.
+    Motion,
+    /// Index of the instance, `-1` on a miss.
+    InstanceId,
+    /// Index of the material, `-1` on a miss.
+    MaterialId,
+    /// Luminance variance estimated by the denoiser.
+    ///
+    /// Only available in the [`BlitMode::DenoisedPathrace`] and
+    /// [`BlitMode::Temporal`] modes, where the moments are accumulated.
+    Variance,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 8] = [
+        Aov::Beauty,
+        Aov::Albedo,
+        Aov::ShadingNormal,
+        Aov::Depth,
+        Aov::Motion,
+        Aov::InstanceId,
+        Aov::MaterialId,
+        Aov::Variance,
+    ];
+
+    /// Number of floats per pixel.
+    pub fn channels(&self) -> usize {
+        match self {
+            Aov::Beauty | Aov::Albedo | Aov::ShadingNormal => 3,
+            Aov::Motion => 2,
+            Aov::Depth | Aov::InstanceId | Aov::MaterialId | Aov::Variance => 1,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BlitMode {
     Pahtrace,
@@ -173,14 +331,25 @@ pub struct Renderer {
     intersection_buffer: gpu::Buffer<Intersection>,
 
     camera_uniforms: gpu::Buffer<Camera>,
+    ray_camera_uniforms: gpu::Buffer<CameraUniforms>,
+    camera_params: CameraParams,
     global_uniforms: PerDrawUniforms,
     global_uniforms_buffer: gpu::Buffer<PerDrawUniforms>,
     radiance_parameters_buffer: gpu::Buffer<RadianceParameters>,
 
     pub shaders: ShaderCache,
     pub passes: Passes,
+    ray_generation: RayGenerationPass,
+    russian_roulette: RussianRoulettePass,
+    bounces: BounceSettings,
+    auto_exposure: AutoExposurePass,
+    bloom: BloomPass,
+    tonemap: TonemapPass,
+    aov: AovPass,
+    tonemapping: TonemapSettings,
 
     asvgf: Option<ASVGF>,
+    upscaler: Option<TemporalUpscaler>,
 
     geometry_bindgroup_layout: albedo_rtx::RTGeometryBindGroupLayout,
     surface_bindgroup_layout: albedo_rtx::RTSurfaceBindGroupLayout,
@@ -189,18 +358,26 @@ pub struct Renderer {
 
     frame_bindgroups: Option<BindGroups>,
     debug_blit_bindgroup: Vec<wgpu::BindGroup>,
+    /// Bloom prefilter bind groups of the views in `debug_blit_bindgroup`.
+    bloom_debug_bindgroup: Vec<wgpu::BindGroup>,
+    /// Auto-exposure bind groups reading the images of `debug_blit_bindgroup`.
+    exposure_debug_bindgroup: Vec<wgpu::BindGroup>,
 
     // Textures
     texture_blue_noise: Option<wgpu::TextureView>,
 
     size: (u32, u32),
+    /// Size requested by the last resize, before applying the resolution scale.
+    target_size: (u32, u32),
 
     mode: BlitMode,
     frame_back: bool,
 
     prev_model_to_screen: glam::Mat4,
+    /// View transform of the last [`Renderer::raytrace`].
+    view_transform: glam::Mat4,
 
-    pub downsample_factor: f32,
+    pub(crate) downsample_factor: f32,
     pub accumulate: bool,
     pub queries: gpu::Queries,
 }
@@ -229,6 +406,12 @@ impl Renderer {
         let geometry_bindgroup_layout = albedo_rtx::RTGeometryBindGroupLayout::new(device);
         let surface_bindgroup_layout = albedo_rtx::RTSurfaceBindGroupLayout::new(device);
         let render_targets = RenderTargets::new(device, size);
+        let mut auto_exposure = AutoExposurePass::new(device);
+        auto_exposure.create_bind_groups(device, [&render_targets.main, &render_targets.second]);
+        let mut bloom = BloomPass::new(device);
+        bloom.create_bind_groups(device, size, [&render_targets.main, &render_targets.second]);
+        let mut tonemap = TonemapPass::new(device, swapchain_format, auto_exposure.state());
+        tonemap.set_bloom_texture(device, bloom.output());
 
         let intersection_buffer = gpu::Buffer::new_storage(device, pixel_count, None);
         let ray_buffer: gpu::Buffer<Ray> = gpu::Buffer::new_storage(
@@ -253,7 +436,6 @@ impl Renderer {
         ));
 
         let passes = Passes {
-            rays: passes::RayPass::new(device, &shaders, None),
             intersection: passes::IntersectorPass::new(
                 device,
                 &shaders,
@@ -274,7 +456,6 @@ impl Renderer {
                 &surface_bindgroup_layout,
             ),
             accumulation: passes::AccumulationPass::new(device, &shaders),
-            blit: passes::BlitPass::new(device, &shaders, swapchain_format),
             lightmap: passes::LightmapPass::new(device, &shaders, swapchain_format),
             blit_texture: passes::BlitTexturePass::new(device, &shaders, swapchain_format),
         };
@@ -283,6 +464,8 @@ impl Renderer {
             render_targets,
 
             camera_uniforms: gpu::Buffer::new_uniform(device, 1, None),
+            ray_camera_uniforms: gpu::Buffer::new_uniform(device, 1, None),
+            camera_params: CameraParams::default(),
             global_uniforms: PerDrawUniforms {
                 frame_count: 1,
                 seed: 0,
@@ -293,12 +476,21 @@ impl Renderer {
             intersection_buffer,
 
             asvgf,
+            upscaler: None,
 
             global_uniforms_buffer: gpu::Buffer::new_uniform(device, 1, None),
             radiance_parameters_buffer: gpu::Buffer::new_uniform(device, 1, None),
 
             shaders,
             passes,
+            ray_generation: RayGenerationPass::new(device),
+            russian_roulette: RussianRoulettePass::new(device),
+            bounces: BounceSettings::default(),
+            auto_exposure,
+            bloom,
+            tonemap,
+            aov: AovPass::new(device),
+            tonemapping: TonemapSettings::default(),
 
             geometry_bindgroup_layout,
             surface_bindgroup_layout,
@@ -307,16 +499,20 @@ impl Renderer {
 
             frame_bindgroups: None,
             debug_blit_bindgroup: Vec::new(),
+            bloom_debug_bindgroup: Vec::new(),
+            exposure_debug_bindgroup: Vec::new(),
 
             texture_blue_noise: None,
 
             size,
+            target_size: original_size,
             downsample_factor,
 
             frame_back: true,
             mode: BlitMode::Pahtrace,
 
             prev_model_to_screen: glam::Mat4::IDENTITY,
+            view_transform: glam::Mat4::IDENTITY,
 
             queries: gpu::Queries::new(device, QueriesOptions::new(10)),
             accumulate: false,
@@ -330,6 +526,7 @@ impl Renderer {
         probe: Option<&ProbeGPU>,
         size: (u32, u32),
     ) {
+        self.target_size = size;
         self.size = get_downsampled_size(&size, self.downsample_factor);
 
         let pixel_count: u64 = self.size.0 as u64 * self.size.1 as u64;
@@ -344,6 +541,16 @@ impl Renderer {
         self.intersection_buffer = gpu::Buffer::new_storage(device, pixel_count, None);
         // TODO: Only resize if bigger.
         self.render_targets = RenderTargets::new(device, self.size);
+        self.auto_exposure.create_bind_groups(
+            device,
+            [&self.render_targets.main, &self.render_targets.second],
+        );
+        self.bloom.create_bind_groups(
+            device,
+            self.size,
+            [&self.render_targets.main, &self.render_targets.second],
+        );
+        self.tonemap.set_bloom_texture(device, self.bloom.output());
         if self.asvgf.is_some() {
             self.asvgf = Some(ASVGF::new(
                 device,
@@ -353,6 +560,9 @@ impl Renderer {
                 &self.ray_buffer,
             ));
         }
+        if self.upscaler.is_some() {
+            self.upscaler = Some(self.create_upscaler(device));
+        }
         self.set_resources(device, scene_resources, probe);
         self.debug_blit_bindgroup.clear(); // Re-create the bindgroup
     }
@@ -394,11 +604,21 @@ impl Renderer {
         encoder: &mut wgpu::CommandEncoder,
         queue: &wgpu::Queue,
         view_transform: &Mat4,
+        camera_params: &CameraParams,
     ) {
-        const STATIC_NUM_BOUNCES: u32 = 3;
-        const MOVING_NUM_BOUNCES: u32 = 3;
-
         self.frame_back = !self.frame_back;
+        self.view_transform = *view_transform;
+
+        // Accumulated samples were traced with the previous camera model.
+        if *camera_params != self.camera_params {
+            self.camera_params = *camera_params;
+            self.reset_accumulation(queue);
+            // The projection might not support the denoiser anymore.
+            self.debug_blit_bindgroup.clear();
+            if let Some(upscaler) = self.upscaler.as_mut() {
+                upscaler.reset();
+            }
+        }
 
         let bindgroups = &self.frame_bindgroups;
         let bindgroups = match bindgroups {
@@ -407,10 +627,11 @@ impl Renderer {
         };
 
         let nb_bounces = if !self.accumulate {
-            MOVING_NUM_BOUNCES
+            self.bounces.moving
         } else {
-            STATIC_NUM_BOUNCES
-        };
+            self.bounces.converging
+        }
+        .min(MAX_BOUNCES);
 
         // Step 1:
         //     * Update the frame uniforms.
@@ -428,11 +649,23 @@ impl Renderer {
             let mut camera = Camera {
                 ..Default::default()
             };
+            camera.v_fov = camera_params.v_fov;
             camera.dimensions = [self.size.0, self.size.1];
             camera.set_transform(&view_transform);
             camera
         };
         self.camera_uniforms.update(&queue, &[camera]);
+        self.ray_camera_uniforms.update(
+            queue,
+            &[CameraUniforms::new(
+                view_transform,
+                camera_params,
+                self.size,
+                self.upscale_source()
+                    .and(self.upscaler.as_ref())
+                    .map(|upscaler| upscaler.jitter()),
+            )],
+        );
         self.global_uniforms.dimensions = [self.size.0, self.size.1];
         self.global_uniforms_buffer
             .update(&queue, &[self.global_uniforms]);
@@ -442,8 +675,7 @@ impl Renderer {
         // Generate a ray struct for every fragment.
 
         self.queries.start("ray generation", encoder);
-        self.passes
-            .rays
+        self.ray_generation
             .dispatch(encoder, &bindgroups.generate_ray_pass, dispatch_size);
         self.queries.end(encoder);
 
@@ -482,6 +714,10 @@ impl Renderer {
 
         // Alternate between intersection & shading.
         let start_bounce = if self.asvgf.is_none() { 0 } else { 1 };
+        if self.bounces.russian_roulette.is_some() {
+            self.russian_roulette
+                .update(queue, self.size, self.global_uniforms.seed, nb_bounces);
+        }
         for i in start_bounce..nb_bounces {
             // @todo: Use dynamic offset
             self.global_uniforms.seed += 1;
@@ -507,9 +743,18 @@ impl Renderer {
                 dispatch_size,
             );
             // self.queries.end(encoder);
+
+            if matches!(self.bounces.russian_roulette, Some(start) if i + 1 >= start) {
+                self.russian_roulette.dispatch(
+                    encoder,
+                    &bindgroups.russian_roulette_pass,
+                    i,
+                    dispatch_size,
+                );
+            }
         }
 
-        match self.mode {
+        match self.effective_mode() {
             BlitMode::DenoisedPathrace => {
                 let asvgf = self.asvgf.as_mut().unwrap();
                 self.queries.start("asvgf", encoder);
@@ -539,9 +784,25 @@ impl Renderer {
             _ => {}
         }
 
-        if let Some(_) = self.asvgf.as_mut() {
+        if let (Some(source), Some(upscaler), Some(asvgf)) = (
+            self.upscale_source(),
+            self.upscaler.as_mut(),
+            self.asvgf.as_ref(),
+        ) {
+            self.queries.start("temporal upscale", encoder);
+            upscaler.dispatch(
+                encoder,
+                queue,
+                source,
+                asvgf.curr_frame(),
+                self.frame_back as usize,
+            );
+            self.queries.end(encoder);
+        }
+
+        if let (Some(_), Some(projection)) = (&self.asvgf, camera_params.projection(self.size)) {
             let inv_view = view_transform.inverse();
-            let world_to_screen = camera.perspective(0.01, 100.0) * inv_view;
+            let world_to_screen = projection * inv_view;
             self.prev_model_to_screen = world_to_screen;
         }
 
@@ -554,56 +815,111 @@ impl Renderer {
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
     ) {
-        if self.debug_blit_bindgroup.is_empty() {
-            match self.mode {
-                BlitMode::DenoisedPathrace => {
-                    self.debug_blit_bindgroup = self.create_debug_bindgroup(
-                        device,
-                        &self.render_targets.main,
-                        &self.render_targets.main,
-                    );
-                }
-                BlitMode::Temporal => {
-                    let textures = &self.asvgf.as_ref().unwrap().resources.pingpong;
-                    self.debug_blit_bindgroup = self.create_debug_bindgroup(
-                        device,
-                        &textures[0].radiance,
-                        &textures[1].radiance,
-                    );
+        let mode = self.effective_mode();
+        self.create_debug_bind_groups(device);
+
+        let index: usize = self.frame_back as usize;
+        if self.tonemapping.bloom.is_some() {
+            match mode {
+                BlitMode::Pahtrace => {
+                    self.bloom
+                        .dispatch(encoder, self.bloom.accumulated(self.accumulated_index()));
                 }
-                BlitMode::GBuffer => {
-                    let textures = &self.asvgf.as_ref().unwrap().resources.pingpong;
-                    self.debug_blit_bindgroup = self.create_debug_bindgroup(
-                        device,
-                        &textures[0].gbuffer,
-                        &textures[1].gbuffer,
-                    );
+                BlitMode::DenoisedPathrace | BlitMode::Temporal => {
+                    self.bloom
+                        .dispatch(encoder, &self.bloom_debug_bindgroup[index]);
                 }
-                BlitMode::MotionVector => {
-                    let res = &self.asvgf.as_ref().unwrap().resources;
-                    self.debug_blit_bindgroup =
-                        self.create_debug_bindgroup(device, &res.motion, &res.motion);
-                }
-                _ => {}
+                BlitMode::GBuffer | BlitMode::MotionVector => {}
             }
         }
+        match mode {
+            BlitMode::Pahtrace => {
+                self.tonemap
+                    .draw(encoder, view, self.accumulated_blit_bindgroup(), false);
+            }
+            BlitMode::DenoisedPathrace | BlitMode::Temporal => {
+                self.tonemap
+                    .draw(encoder, view, &self.debug_blit_bindgroup[index], false);
+            }
+            BlitMode::GBuffer | BlitMode::MotionVector => {
+                self.passes
+                    .blit_texture
+                    .draw(encoder, &view, &self.debug_blit_bindgroup[index]);
+            }
+        }
+    }
 
-        if self.mode != BlitMode::Pahtrace {
-            let index: usize = self.frame_back as usize;
-            self.passes
-                .blit_texture
-                .draw(encoder, &view, &self.debug_blit_bindgroup[index]);
+    /// Creates the bind groups reading the image displayed by [`Self::blit`],
+    /// when it isn't the accumulated radiance.
+    fn create_debug_bind_groups(&mut self, device: &Device) {
+        if !self.debug_blit_bindgroup.is_empty() {
             return;
         }
+        let sources = match self.effective_mode() {
+            _ if self.upscale_source().is_some() => {
+                let upscaler = self.upscaler.as_ref().unwrap();
+                Some([upscaler.output(0), upscaler.output(1)])
+            }
+            BlitMode::DenoisedPathrace => {
+                let main = &self.render_targets.main;
+                Some([main, main])
+            }
+            BlitMode::Temporal => {
+                let textures = &self.asvgf.as_ref().unwrap().resources.pingpong;
+                Some([&textures[0].radiance, &textures[1].radiance])
+            }
+            BlitMode::GBuffer => {
+                let textures = &self.asvgf.as_ref().unwrap().resources.pingpong;
+                self.debug_blit_bindgroup =
+                    self.create_debug_bindgroup(device, &textures[0].gbuffer, &textures[1].gbuffer);
+                None
+            }
+            BlitMode::MotionVector => {
+                let res = &self.asvgf.as_ref().unwrap().resources;
+                self.debug_blit_bindgroup =
+                    self.create_debug_bindgroup(device, &res.motion, &res.motion);
+                None
+            }
+            BlitMode::Pahtrace => None,
+        };
+        if let Some(sources) = sources {
+            self.debug_blit_bindgroup =
+                self.create_tonemap_bindgroup(device, sources[0], sources[1]);
+            self.bloom_debug_bindgroup = sources
+                .map(|view| self.bloom.create_source_bind_group(device, view))
+                .into();
+            self.exposure_debug_bindgroup = sources
+                .map(|view| self.auto_exposure.create_source_bind_group(device, view))
+                .into();
+        }
+    }
 
-        let bindgroups: &BindGroups = self.frame_bindgroups.as_ref().unwrap();
+    /// Index of the render target last written by the accumulation pass.
+    fn accumulated_index(&self) -> usize {
+        1 - (self.global_uniforms.frame_count % 2) as usize
+    }
 
-        let bindgroup = if self.global_uniforms.frame_count % 2 != 0 {
-            &bindgroups.blit_pass
+    /// Blit bind group reading the render target last written by the
+    /// accumulation pass.
+    fn accumulated_blit_bindgroup(&self) -> &wgpu::BindGroup {
+        let bindgroups: &BindGroups = self.frame_bindgroups.as_ref().unwrap();
+        if self.global_uniforms.frame_count % 2 != 0 {
+            &bindgroups.tonemap_pass
         } else {
-            &bindgroups.blit_pass2
-        };
-        self.passes.blit.draw(encoder, &view, bindgroup);
+            &bindgroups.tonemap_pass2
+        }
+    }
+
+    /// Finds the surface under the pixel `(x, y)` of the last
+    /// [`Self::raytrace`], with the origin at the top left of the render.
+    ///
+    /// The ray is cast on the CPU, against the geometry of `scene`.
+    pub fn pick(&self, scene: &Scene, x: f32, y: f32) -> Option<RayHit> {
+        let uv = glam::Vec2::new(x / self.size.0.max(1) as f32, y / self.size.1.max(1) as f32);
+        let (origin, dir) = self
+            .camera_params
+            .primary_ray(&self.view_transform, self.size, uv);
+        scene.raycast(origin, dir, f32::INFINITY)
     }
 
     pub fn reset_accumulation(&mut self, queue: &wgpu::Queue) {
@@ -672,12 +988,167 @@ impl Renderer {
         )
     }
 
+    /// Changes the path length, restarting accumulation if it differs.
+    pub fn set_bounces(&mut self, queue: &wgpu::Queue, bounces: BounceSettings) {
+        if self.bounces == bounces {
+            return;
+        }
+        self.bounces = bounces;
+        self.reset_accumulation(queue);
+    }
+
+    pub fn bounces(&self) -> &BounceSettings {
+        &self.bounces
+    }
+
+    /// Changes the exposure and tone curve used by [`Self::blit`] and
+    /// [`Self::read_pixels`].
+    pub fn set_tonemapping(&mut self, queue: &wgpu::Queue, settings: TonemapSettings) {
+        if self.tonemapping == settings {
+            return;
+        }
+        if self.tonemapping.auto_exposure.is_none() && settings.auto_exposure.is_some() {
+            self.auto_exposure.reset();
+        }
+        if let Some(bloom) = &settings.bloom {
+            self.bloom.update(queue, bloom);
+        }
+        self.tonemapping = settings;
+        self.tonemap.update(queue, &settings);
+    }
+
+    /// Adapts the auto-exposure to the last frame, `delta` seconds after the
+    /// previous one. Does nothing if auto-exposure is disabled.
+    ///
+    /// The histogram is built from the image displayed by [`Self::blit`].
+    pub fn adapt_exposure(
+        &mut self,
+        device: &Device,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        delta: f32,
+    ) {
+        let Some(settings) = self.tonemapping.auto_exposure else {
+            return;
+        };
+        self.create_debug_bind_groups(device);
+        self.auto_exposure.update(queue, &settings, delta);
+
+        // The upscaler reconstructs the image at the target size.
+        let size = match self.upscale_source() {
+            Some(_) => self.target_size,
+            None => self.size,
+        };
+        let source = match self.effective_mode() {
+            _ if self.upscale_source().is_some() => {
+                &self.exposure_debug_bindgroup[self.frame_back as usize]
+            }
+            BlitMode::Pahtrace => self.auto_exposure.accumulated(self.accumulated_index()),
+            BlitMode::DenoisedPathrace | BlitMode::Temporal => {
+                &self.exposure_debug_bindgroup[self.frame_back as usize]
+            }
+            BlitMode::GBuffer | BlitMode::MotionVector => return,
+        };
+        self.auto_exposure.dispatch(encoder, source, size);
+    }
+
+    pub fn tonemapping(&self) -> &TonemapSettings {
+        &self.tonemapping
+    }
+
+    /// Grades the tonemapped image with `lut`, in [`Self::blit`] and
+    /// [`Self::read_pixels`]. `None` disables grading.
+    pub fn set_lut(&mut self, device: &Device, queue: &wgpu::Queue, lut: Option<&Lut>) {
+        self.tonemap.set_lut(device, queue, lut);
+    }
+
     pub fn set_blit_mode(&mut self, mode: BlitMode) {
         if self.mode == mode {
             return;
         }
         self.mode = mode;
         self.debug_blit_bindgroup.clear(); // Re-create
+        if let Some(upscaler) = self.upscaler.as_mut() {
+            upscaler.reset();
+        }
+    }
+
+    /// Blit mode actually used.
+    ///
+    /// The denoiser relies on reprojection, non-linear projections thus
+    /// fall back to plain accumulation.
+    pub fn effective_mode(&self) -> BlitMode {
+        match self.mode {
+            BlitMode::DenoisedPathrace | BlitMode::Temporal
+                if self.camera_params.projection(self.size).is_none() =>
+            {
+                BlitMode::Pahtrace
+            }
+            mode => mode,
+        }
+    }
+
+    /// Enables the reconstruction of a full resolution image from the
+    /// downsampled render, in the denoised and temporal blit modes.
+    pub fn set_temporal_upscaling(&mut self, device: &Device, enabled: bool) {
+        if self.upscaler.is_some() == enabled {
+            return;
+        }
+        self.upscaler = enabled.then(|| self.create_upscaler(device));
+        self.debug_blit_bindgroup.clear(); // Re-create
+    }
+
+    pub fn temporal_upscaling(&self) -> bool {
+        self.upscaler.is_some()
+    }
+
+    /// Image reconstructed by the upscaler this frame, if any.
+    fn upscale_source(&self) -> Option<UpscaleSource> {
+        self.upscaler.as_ref()?;
+        match self.effective_mode() {
+            BlitMode::DenoisedPathrace => Some(UpscaleSource::Denoised),
+            BlitMode::Temporal => Some(UpscaleSource::Temporal),
+            _ => None,
+        }
+    }
+
+    fn create_upscaler(&self, device: &Device) -> TemporalUpscaler {
+        let mut upscaler = TemporalUpscaler::new(device, self.target_size);
+        if let Some(asvgf) = &self.asvgf {
+            upscaler.create_bind_groups(device, asvgf, &self.render_targets.main);
+        }
+        upscaler
+    }
+
+    pub fn blit_mode(&self) -> BlitMode {
+        self.mode
+    }
+
+    /// Changes the ratio between the render size and the size given to
+    /// [`Self::resize`], reallocating the render targets.
+    pub fn set_resolution_scale(
+        &mut self,
+        device: &Device,
+        scene_resources: &SceneGPU,
+        probe: Option<&ProbeGPU>,
+        scale: f32,
+    ) {
+        let scale = scale.clamp(0.05, 1.0);
+        if self.downsample_factor == scale {
+            return;
+        }
+        self.downsample_factor = scale;
+        self.resize(device, scene_resources, probe, self.target_size);
+    }
+
+    pub fn resolution_scale(&self) -> f32 {
+        self.downsample_factor
+    }
+
+    /// Size given to the last [`Self::resize`], before applying the
+    /// resolution scale.
+    pub fn target_size(&self) -> (u32, u32) {
+        self.target_size
     }
 
     pub fn get_size(&self) -> &(u32, u32) {
@@ -689,6 +1160,21 @@ impl Renderer {
         device: &Device,
         scene_resources: &SceneGPU,
         probe: Option<&ProbeGPU>,
+    ) {
+        self.frame_bindgroups = Some(self.create_bind_groups(device));
+        self.set_scene_resources(device, scene_resources, probe);
+        self.global_uniforms.frame_count = 1;
+    }
+
+    /// Re-creates the bind groups referencing the scene buffers only.
+    ///
+    /// Cheaper than [`Renderer::set_resources`], used when a scene buffer got
+    /// re-allocated by an incremental update.
+    pub fn set_scene_resources(
+        &mut self,
+        device: &Device,
+        scene_resources: &SceneGPU,
+        probe: Option<&ProbeGPU>,
     ) {
         let probe_view = match probe {
             Some(p) => &p.view,
@@ -701,7 +1187,6 @@ impl Renderer {
             _ => device.default_textures().filterable_2d(),
         };
 
-        self.frame_bindgroups = Some(self.create_bind_groups(device));
         self.geometry_bindgroup = Some(self.geometry_bindgroup_layout.create_bindgroup(
             device,
             scene_resources.bvh_buffer.as_storage_slice().unwrap(),
@@ -721,93 +1206,168 @@ impl Renderer {
             noise_texture,
             self.radiance_parameters_buffer.as_uniform_slice().unwrap(),
         ));
-        self.global_uniforms.frame_count = 1;
+        self.aov.set_scene(
+            scene_resources.instance_buffer.inner(),
+            scene_resources.materials_buffer.inner(),
+        );
     }
 
+    /// Reads back the accumulated image as sRGB RGBA8, tonemapped like
+    /// [`Self::blit`].
     pub async fn read_pixels(
         &self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
     ) -> Result<Vec<u8>, Error> {
-        let alignment = albedo_backend::Alignment2D::texture_buffer_copy(
-            self.size.0 as usize,
-            std::mem::size_of::<u32>(),
-        );
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Read Pixel Encoder"),
         });
         let (width, height) = self.size;
-        let gpu_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: height as u64 * alignment.padded_bytes() as u64,
-            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-        let texture_extent = wgpu::Extent3d {
-            width: width as u32,
-            height: height as u32,
-            depth_or_array_layers: 1,
-        };
         let texture = device.create_texture(&wgpu::TextureDescriptor {
-            size: texture_extent,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format: READBACK_FORMAT,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
             label: None,
             view_formats: &[],
         });
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        // @todo: this re-create shaders + pipeline layout + life.
-        let blit_pass =
-            passes::BlitPass::new(device, &self.shaders, wgpu::TextureFormat::Rgba8UnormSrgb);
-        blit_pass.draw(
-            &mut encoder,
-            &view,
-            &self.frame_bindgroups.as_ref().unwrap().blit_pass,
-        );
+        if self.tonemapping.bloom.is_some() {
+            self.bloom.dispatch(
+                &mut encoder,
+                self.bloom.accumulated(self.accumulated_index()),
+            );
+        }
+        self.tonemap
+            .draw(&mut encoder, &view, self.accumulated_blit_bindgroup(), true);
 
-        encoder.copy_texture_to_buffer(
-            texture.as_image_copy(),
-            wgpu::TexelCopyBufferInfo {
-                buffer: &gpu_buffer,
-                layout: wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(alignment.padded_bytes() as u32),
-                    rows_per_image: None,
-                },
-            },
-            texture_extent,
-        );
-        queue.submit(Some(encoder.finish()));
-
-        let buffer_slice = gpu_buffer.slice(..);
-        // Sets the buffer up for mapping, sending over the result of the mapping back to us when it is finished.
-        let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
-
-        device.poll(wgpu::Maintain::Wait);
-
-        if let Some(Ok(())) = receiver.receive().await {
-            let padded_buffer = buffer_slice.get_mapped_range();
-            let mut bytes: Vec<u8> = vec![0; alignment.unpadded_bytes_per_row * height as usize];
-            // from the padded_buffer we write just the unpadded bytes into the image
-            for (padded, bytes) in padded_buffer
-                .chunks_exact(alignment.padded_bytes_per_row)
-                .zip(bytes.chunks_exact_mut(alignment.unpadded_bytes_per_row))
-            {
-                bytes.copy_from_slice(&padded[..alignment.unpadded_bytes_per_row]);
-            }
-            // With the current interface, we have to make sure all mapped views are
-            // dropped before we unmap the buffer.
-            drop(padded_buffer);
-            gpu_buffer.unmap();
-            Ok(bytes)
+        read_texture(device, queue, encoder, &texture, std::mem::size_of::<u32>()).await
+    }
+
+    /// Reads back the histogram of a previous [`Self::adapt_exposure`],
+    /// without blocking.
+    ///
+    /// Returns `None` until the GPU copy started by an earlier call is
+    /// available, it is thus meant to be polled once per frame.
+    pub fn poll_luminance_histogram(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<Result<LuminanceHistogram, Error>> {
+        let state = match self.auto_exposure.poll_state(device, queue)? {
+            Ok(state) => state,
+            Err(e) => return Some(Err(e)),
+        };
+        let settings = self.tonemapping.auto_exposure.unwrap_or_default();
+        Some(Ok(LuminanceHistogram {
+            bins: state.bins.to_vec(),
+            min_log_luminance: settings.min_log_luminance,
+            max_log_luminance: settings.max_log_luminance,
+            average_log_luminance: state.average_log_luminance,
+            exposure: state.exposure,
+        }))
+    }
+
+    /// Reads back the accumulated radiance, as linear RGBA floats.
+    ///
+    /// Unlike [`Self::read_pixels`], the values aren't tonemapped nor quantized.
+    pub async fn read_radiance(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<Vec<f32>, Error> {
+        let encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Read Radiance Encoder"),
+        });
+        // Same parity as `accumulated_blit_bindgroup`.
+        let texture = if self.global_uniforms.frame_count % 2 == 1 {
+            &self.render_targets.main_texture
         } else {
-            Err(Error::TextureToBufferReadFail)
+            &self.render_targets.second_texture
+        };
+        let bytes = read_texture(
+            device,
+            queue,
+            encoder,
+            texture,
+            std::mem::size_of::<[f32; 4]>(),
+        )
+        .await?;
+        Ok(bytemuck::pod_collect_to_vec(&bytes))
+    }
+
+    /// Reads back `aov`, as [`Aov::channels`] floats per pixel.
+    ///
+    /// The buffers are at the render resolution, rows from top to bottom.
+    pub async fn read_aov(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        aov: Aov,
+    ) -> Result<Vec<f32>, Error> {
+        let denoised = matches!(
+            self.effective_mode(),
+            BlitMode::DenoisedPathrace | BlitMode::Temporal
+        );
+        if aov == Aov::Variance && !denoised {
+            return Err(Error::AovUnavailable(String::from(
+                "the variance is only estimated by the denoiser",
+            )));
         }
+        let pixels = match aov {
+            Aov::Beauty => self.read_radiance(device, queue).await?,
+            _ => {
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Read AOV Encoder"),
+                });
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    size: wgpu::Extent3d {
+                        width: self.size.0,
+                        height: self.size.1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: AOV_FORMAT,
+                    usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+                    label: Some("AOV Target"),
+                    view_formats: &[],
+                });
+                // G-buffer and moments written by the last frame.
+                let asvgf = self.asvgf.as_ref().unwrap();
+                let resources = &asvgf.resources;
+                let current = &resources.pingpong[asvgf.curr_frame()];
+                let sources = AovSources {
+                    gbuffer: &current.gbuffer,
+                    motion: &resources.motion,
+                    moments: &current.moments,
+                };
+                self.aov
+                    .dispatch(device, queue, &mut encoder, aov, &sources, &texture);
+                let bytes = read_texture(
+                    device,
+                    queue,
+                    encoder,
+                    &texture,
+                    std::mem::size_of::<[f32; 4]>(),
+                )
+                .await?;
+                bytemuck::pod_collect_to_vec(&bytes)
+            }
+        };
+        let channels = aov.channels();
+        Ok(pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| pixel[..channels].iter().copied())
+            .collect())
     }
 
     fn create_bind_groups(&self, device: &Device) -> BindGroups {
@@ -828,15 +1388,34 @@ impl Renderer {
             &self.render_targets,
             &resources,
             &denoise_res,
-            &self.passes.rays,
+            self.ray_generation.create_frame_bind_groups(
+                device,
+                &self.ray_buffer,
+                &self.ray_camera_uniforms,
+                &self.global_uniforms_buffer,
+            ),
+            self.russian_roulette
+                .create_frame_bind_groups(device, &self.ray_buffer),
             &self.passes.intersection,
             &self.passes.shading,
             &self.passes.primary_rays,
             &self.passes.accumulation,
-            &self.passes.blit,
+            &self.tonemap,
         )
     }
 
+    fn create_tonemap_bindgroup(
+        &self,
+        device: &Device,
+        curr: &wgpu::TextureView,
+        prev: &wgpu::TextureView,
+    ) -> Vec<wgpu::BindGroup> {
+        vec![
+            self.tonemap.create_frame_bind_groups(device, curr),
+            self.tonemap.create_frame_bind_groups(device, prev),
+        ]
+    }
+
     fn create_debug_bindgroup(
         &self,
         device: &Device,
@@ -857,3 +1436,62 @@ impl Renderer {
         ]
     }
 }
+
+/// Copies `texture` into a buffer after the commands of `encoder`, and reads
+/// it back without row padding.
+async fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mut encoder: wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    bytes_per_pixel: usize,
+) -> Result<Vec<u8>, Error> {
+    let (width, height) = (texture.width(), texture.height());
+    let alignment =
+        albedo_backend::Alignment2D::texture_buffer_copy(width as usize, bytes_per_pixel);
+    let gpu_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: height as u64 * alignment.padded_bytes() as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &gpu_buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(alignment.padded_bytes() as u32),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = gpu_buffer.slice(..);
+    // Sets the buffer up for mapping, sending over the result of the mapping back to us when it is finished.
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+
+    device.poll(wgpu::Maintain::Wait);
+
+    if let Some(Ok(())) = receiver.receive().await {
+        let padded_buffer = buffer_slice.get_mapped_range();
+        let mut bytes: Vec<u8> = vec![0; alignment.unpadded_bytes_per_row * height as usize];
+        // from the padded_buffer we write just the unpadded bytes into the image
+        for (padded, bytes) in padded_buffer
+            .chunks_exact(alignment.padded_bytes_per_row)
+            .zip(bytes.chunks_exact_mut(alignment.unpadded_bytes_per_row))
+        {
+            bytes.copy_from_slice(&padded[..alignment.unpadded_bytes_per_row]);
+        }
+        // With the current interface, we have to make sure all mapped views are
+        // dropped before we unmap the buffer.
+        drop(padded_buffer);
+        gpu_buffer.unmap();
+        Ok(bytes)
+    } else {
+        Err(Error::TextureToBufferReadFail)
+    }
+}
@@ -1,6 +1,14 @@
+use std::ops::Range;
+
 use albedo_backend::gpu::{self, Atlas2D, TextureAtlas, TextureId};
-use albedo_rtx::uniforms::{BVHNode, Instance, Light, Material, Vertex};
-use albedo_rtx::{BLASArray, BVHPrimitive};
+use albedo_rtx::uniforms::{BVHNode, Instance, Light, Vertex, INVALID_INDEX};
+use albedo_rtx::{BLASArray, BVHPrimitive, IndexedMeshDescriptor, MeshDescriptor};
+
+use crate::animation::{Animation, ChannelValues, MorphedMesh, Node, Skin, SkinnedMesh};
+use crate::camera::SceneCamera;
+use crate::tlas::{Aabb, TLAS};
+
+pub use albedo_rtx::uniforms::Material;
 
 pub struct ImageData {
     data: Vec<u8>,
@@ -27,16 +35,200 @@ impl ImageData {
     }
 }
 
+/// Stable reference to an instance of the scene.
+///
+/// Handles remain valid when other instances are removed, unlike indices
+/// into `blas.instances`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstanceHandle(u32);
+
+/// Reference to a material of the scene.
+///
+/// Materials are never removed, the handle is thus the index in `materials`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialHandle(pub u32);
+
+/// Reference to a light of the scene.
+///
+/// Lights are never removed, the handle is thus the index in `lights`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LightHandle(pub u32);
+
+#[derive(Default)]
+struct HandleTable {
+    // Handle to dense index, `None` once the entry is removed.
+    slots: Vec<Option<u32>>,
+    // Dense index to handle.
+    owners: Vec<u32>,
+}
+
+impl HandleTable {
+    fn insert(&mut self) -> u32 {
+        let handle = self.slots.len() as u32;
+        self.slots.push(Some(self.owners.len() as u32));
+        self.owners.push(handle);
+        handle
+    }
+
+    /// Registers entries pushed without going through the handle table.
+    fn sync(&mut self, count: usize) {
+        while self.owners.len() < count {
+            self.insert();
+        }
+    }
+
+    fn get(&self, handle: u32) -> Option<usize> {
+        self.slots
+            .get(handle as usize)
+            .copied()
+            .flatten()
+            .map(|i| i as usize)
+    }
+
+    /// Mirrors a `Vec::swap_remove` on the dense storage.
+    fn swap_remove(&mut self, handle: u32) -> Option<usize> {
+        let index = self.get(handle)?;
+        self.slots[handle as usize] = None;
+        self.owners.swap_remove(index);
+        if let Some(&moved) = self.owners.get(index) {
+            self.slots[moved as usize] = Some(index as u32);
+        }
+        Some(index)
+    }
+}
+
+/// Ranges of the scene data modified since the last GPU upload.
+#[derive(Clone, Debug, Default)]
+pub struct DirtyRanges {
+    pub instances: Option<Range<usize>>,
+    pub materials: Option<Range<usize>>,
+    pub lights: Option<Range<usize>>,
+    pub bvh_nodes: Option<Range<usize>>,
+    pub bvh_primitives: Option<Range<usize>>,
+    pub vertices: Option<Range<usize>>,
+}
+
+impl DirtyRanges {
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_none()
+            && self.materials.is_none()
+            && self.lights.is_none()
+            && self.bvh_nodes.is_none()
+            && self.bvh_primitives.is_none()
+            && self.vertices.is_none()
+    }
+}
+
+fn extend_range(range: &mut Option<Range<usize>>, start: usize, end: usize) {
+    *range = Some(match range.take() {
+        Some(r) => r.start.min(start)..r.end.max(end),
+        None => start..end,
+    });
+}
+
+/// Geometry of a BLAS entry, kept to re-build it once deformed.
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 4]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+    pub texcoords: Option<Vec<[f32; 2]>>,
+    pub indices: Option<Vec<u32>>,
+}
+
+impl MeshData {
+    fn build(&self, blas: &mut BLASArray) {
+        let mesh = MeshDescriptor {
+            positions: pas::Slice::native(&self.positions),
+            normals: self.normals.as_ref().map(|v| pas::Slice::native(v)),
+            texcoords0: self.texcoords.as_ref().map(|v| pas::Slice::native(v)),
+        };
+        match &self.indices {
+            Some(indices) => blas.add_bvh_indexed(IndexedMeshDescriptor { mesh, indices }),
+            None => blas.add_bvh(mesh),
+        }
+    }
+}
+
+/// Closest intersection found by [`Scene::raycast`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    /// Distance along the ray, in world units.
+    pub distance: f32,
+    /// World space position of the hit.
+    pub position: glam::Vec3,
+    pub instance: InstanceHandle,
+    /// Material of the instance.
+    pub material: MaterialHandle,
+    /// Index of the triangle in the mesh.
+    pub primitive: u32,
+    /// Weights of the second and third vertices of the triangle.
+    pub barycentrics: glam::Vec2,
+}
+
+/// Möller–Trumbore ray / triangle intersection, returns the distance and
+/// barycentrics.
+fn intersect_triangle(
+    origin: glam::Vec3,
+    dir: glam::Vec3,
+    triangle: [glam::Vec3; 3],
+) -> Option<(f32, glam::Vec2)> {
+    let e1 = triangle[1] - triangle[0];
+    let e2 = triangle[2] - triangle[0];
+    let p = dir.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = origin - triangle[0];
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = dir.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(q) * inv_det;
+    (t > 0.0).then_some((t, glam::Vec2::new(u, v)))
+}
+
+// Sizes of the BLAS arrays before an entry was added.
+#[derive(Copy, Clone, Debug, Default)]
+struct BLASOffsets {
+    nodes: usize,
+    primitives: usize,
+    vertices: usize,
+}
+
 pub struct Scene {
     pub materials: Vec<Material>,
     pub blas: BLASArray,
     pub lights: Vec<Light>,
     pub images: Vec<ImageData>,
+    instance_handles: HandleTable,
+    dirty: DirtyRanges,
+    // Local bounds of each BLAS entry.
+    mesh_bounds: Vec<Aabb>,
+    // BLAS entry of each instance.
+    instance_meshes: Vec<u32>,
+    tlas: TLAS,
+    tlas_dirty: bool,
+    // Source of each BLAS entry, `None` for entries added to `blas` directly.
+    meshes: Vec<Option<MeshData>>,
+    mesh_offsets: Vec<BLASOffsets>,
+    pub nodes: Vec<Node>,
+    pub animations: Vec<Animation>,
+    pub skins: Vec<Skin>,
+    pub skinned_meshes: Vec<SkinnedMesh>,
+    pub morphed_meshes: Vec<MorphedMesh>,
+    pub cameras: Vec<SceneCamera>,
 }
 
 impl Default for Scene {
     fn default() -> Self {
-        Self {
+        let mut scene = Self {
             materials: vec![Material {
                 ..Default::default()
             }],
@@ -49,7 +241,652 @@ impl Default for Scene {
             },
             lights: vec![Light::new()],
             images: vec![],
+            instance_handles: HandleTable::default(),
+            dirty: DirtyRanges::default(),
+            mesh_bounds: vec![Aabb::EMPTY],
+            instance_meshes: vec![0],
+            tlas: TLAS::default(),
+            tlas_dirty: true,
+            meshes: vec![None],
+            mesh_offsets: vec![BLASOffsets::default()],
+            nodes: vec![],
+            animations: vec![],
+            skins: vec![],
+            skinned_meshes: vec![],
+            morphed_meshes: vec![],
+            cameras: vec![],
+        };
+        scene.instance_handles.sync(scene.blas.instances.len());
+        scene
+    }
+}
+
+impl Scene {
+    /// Builds the BVH of a mesh, returns its BLAS index.
+    pub fn add_mesh(&mut self, mesh: MeshData) -> u32 {
+        let index = self.blas.entries.len();
+        self.build_mesh(&mesh);
+        // Entries added directly to the BLAS have no source and unknown bounds.
+        self.meshes.resize(index, None);
+        self.meshes.push(Some(mesh));
+        index as u32
+    }
+
+    pub fn mesh(&self, blas_index: u32) -> Option<&MeshData> {
+        self.meshes.get(blas_index as usize)?.as_ref()
+    }
+
+    fn build_mesh(&mut self, mesh: &MeshData) {
+        let index = self.blas.entries.len();
+        let offsets = BLASOffsets {
+            nodes: self.blas.nodes.len(),
+            primitives: self.blas.primitives.len(),
+            vertices: self.blas.vertices.len(),
+        };
+        mesh.build(&mut self.blas);
+
+        self.mesh_offsets.resize(index, BLASOffsets::default());
+        self.mesh_offsets.push(offsets);
+        self.mesh_bounds.resize(index, Aabb::INFINITE);
+        self.mesh_bounds.push(Aabb::from_points(
+            mesh.positions
+                .iter()
+                .map(|p| glam::Vec3::new(p[0], p[1], p[2])),
+        ));
+    }
+
+    /// Re-builds the BLAS entries starting at `first` from their source.
+    ///
+    /// The BLAS arrays are append-only, all entries after `first` are thus
+    /// re-built as well: deformed meshes are cheaper to update when added last.
+    /// Returns `false` if an entry has no source.
+    pub fn rebuild_blas_from(&mut self, first: u32) -> bool {
+        // The first entry is a placeholder without source.
+        let first = (first as usize).max(1);
+        let count = self.blas.entries.len();
+        if first >= count {
+            return true;
+        }
+        if (first..count).any(|i| !matches!(self.meshes.get(i), Some(Some(_))))
+            || self.instance_meshes.len() != self.blas.instances.len()
+        {
+            return false;
+        }
+
+        let offsets = self.mesh_offsets[first];
+        self.blas.entries.truncate(first);
+        self.blas.nodes.truncate(offsets.nodes);
+        self.blas.primitives.truncate(offsets.primitives);
+        self.blas.vertices.truncate(offsets.vertices);
+        self.mesh_offsets.truncate(first);
+        self.mesh_bounds.truncate(first);
+
+        let meshes = std::mem::take(&mut self.meshes);
+        for mesh in meshes[first..].iter().flatten() {
+            self.build_mesh(mesh);
+        }
+        self.meshes = meshes;
+
+        // Instances store offsets into the BLAS arrays and must be re-created.
+        for i in 0..self.blas.instances.len() {
+            let mesh = self.instance_meshes.get(i).copied().unwrap_or(u32::MAX);
+            if mesh == u32::MAX || (mesh as usize) < first {
+                continue;
+            }
+            let instance = self.blas.instances[i];
+            self.blas
+                .add_instance(mesh, instance.model_to_world, instance.material_index);
+            self.blas.instances.swap_remove(i);
+        }
+
+        extend_range(&mut self.dirty.instances, 0, self.blas.instances.len());
+        extend_range(
+            &mut self.dirty.bvh_nodes,
+            offsets.nodes,
+            self.blas.nodes.len(),
+        );
+        extend_range(
+            &mut self.dirty.bvh_primitives,
+            offsets.primitives,
+            self.blas.primitives.len(),
+        );
+        extend_range(
+            &mut self.dirty.vertices,
+            offsets.vertices,
+            self.blas.vertices.len(),
+        );
+        self.tlas_dirty = true;
+        true
+    }
+
+    /// Re-builds a BLAS entry from its source, overwriting the previous build.
+    ///
+    /// Entries store their nodes, primitives and vertices relative to the
+    /// roots referenced by instances, which are thus left untouched. Returns
+    /// `false` if the new build doesn't have the size of the previous one, or
+    /// if the entry has no source: [`Self::rebuild_blas_from`] must be used.
+    fn rebuild_blas_in_place(&mut self, blas_index: u32) -> bool {
+        let index = blas_index as usize;
+        let Some(Some(mesh)) = self.meshes.get(index) else {
+            return false;
+        };
+        let start = self.mesh_offsets[index];
+        let end = match self.meshes.get(index + 1) {
+            Some(Some(_)) => self.mesh_offsets[index + 1],
+            // Entries without source don't record their offsets.
+            Some(None) => return false,
+            None => BLASOffsets {
+                nodes: self.blas.nodes.len(),
+                primitives: self.blas.primitives.len(),
+                vertices: self.blas.vertices.len(),
+            },
+        };
+
+        let mut built = BLASArray {
+            entries: vec![],
+            nodes: vec![],
+            primitives: vec![],
+            vertices: vec![],
+            instances: vec![],
+        };
+        mesh.build(&mut built);
+        if built.nodes.len() != end.nodes - start.nodes
+            || built.primitives.len() != end.primitives - start.primitives
+            || built.vertices.len() != end.vertices - start.vertices
+        {
+            return false;
+        }
+        self.mesh_bounds[index] = Aabb::from_points(
+            mesh.positions
+                .iter()
+                .map(|p| glam::Vec3::new(p[0], p[1], p[2])),
+        );
+
+        self.blas.nodes[start.nodes..end.nodes].copy_from_slice(&built.nodes);
+        self.blas.primitives[start.primitives..end.primitives].copy_from_slice(&built.primitives);
+        self.blas.vertices[start.vertices..end.vertices].copy_from_slice(&built.vertices);
+        extend_range(&mut self.dirty.bvh_nodes, start.nodes, end.nodes);
+        extend_range(
+            &mut self.dirty.bvh_primitives,
+            start.primitives,
+            end.primitives,
+        );
+        extend_range(&mut self.dirty.vertices, start.vertices, end.vertices);
+        self.tlas_dirty = true;
+        true
+    }
+
+    pub fn mesh_bounds(&self, blas_index: u32) -> Aabb {
+        self.mesh_bounds
+            .get(blas_index as usize)
+            .copied()
+            .unwrap_or(Aabb::INFINITE)
+    }
+
+    /// World-space bounds of the instance stored at `index` in `blas.instances`.
+    pub fn instance_bounds(&self, index: usize) -> Aabb {
+        match self.instance_meshes.get(index) {
+            Some(&mesh) => self
+                .mesh_bounds(mesh)
+                .transform(&self.blas.instances[index].model_to_world),
+            None => Aabb::INFINITE,
+        }
+    }
+
+    /// World-space bounds of all instances, ignoring unbounded ones.
+    pub fn bounds(&self) -> Aabb {
+        (0..self.blas.instances.len())
+            .map(|i| self.instance_bounds(i))
+            .filter(|aabb| !aabb.is_empty() && aabb.extent().is_finite())
+            .fold(Aabb::EMPTY, |acc, aabb| acc.union(&aabb))
+    }
+
+    pub fn tlas(&self) -> &TLAS {
+        &self.tlas
+    }
+
+    /// Finds the closest triangle hit by a world space ray, on the CPU.
+    ///
+    /// Uses the top-level BVH when it is up to date, and tests every instance
+    /// otherwise, see [`Self::update_tlas`]. Entries added to `blas` without
+    /// going through [`Self::add_mesh`] are ignored.
+    pub fn raycast(&self, origin: glam::Vec3, dir: glam::Vec3, max_dist: f32) -> Option<RayHit> {
+        let mut closest: Option<RayHit> = None;
+        let mut visit = |index: u32, max_dist: f32| match self.raycast_instance(
+            index as usize,
+            origin,
+            dir,
+            max_dist,
+        ) {
+            Some(hit) => {
+                closest = Some(hit);
+                hit.distance
+            }
+            None => max_dist,
+        };
+        if self.tlas_dirty {
+            let inv_dir = dir.recip();
+            let mut max_dist = max_dist;
+            for index in 0..self.blas.instances.len() {
+                if self
+                    .instance_bounds(index)
+                    .intersect(origin, inv_dir, max_dist)
+                    .is_some()
+                {
+                    max_dist = visit(index as u32, max_dist);
+                }
+            }
+        } else {
+            self.tlas.traverse(origin, dir, max_dist, visit);
+        }
+        closest
+    }
+
+    /// Closest hit of the ray with the instance at `index`, nearer than
+    /// `max_dist`.
+    fn raycast_instance(
+        &self,
+        index: usize,
+        origin: glam::Vec3,
+        dir: glam::Vec3,
+        max_dist: f32,
+    ) -> Option<RayHit> {
+        let mesh = self
+            .instance_meshes
+            .get(index)
+            .and_then(|&blas_index| self.mesh(blas_index))?;
+        // The ray parameter is preserved by the transform, the direction
+        // is thus left unnormalized.
+        let world_to_model = self.blas.instances[index].model_to_world.inverse();
+        let local_origin = world_to_model.transform_point3(origin);
+        let local_dir = world_to_model.transform_vector3(dir);
+
+        let vertex = |i: u32| glam::Vec4::from(mesh.positions[i as usize]).truncate();
+        let triangle_count = match &mesh.indices {
+            Some(indices) => indices.len() / 3,
+            None => mesh.positions.len() / 3,
+        };
+        let mut closest: Option<RayHit> = None;
+        let mut max_dist = max_dist;
+        for primitive in 0..triangle_count {
+            let i = primitive * 3;
+            let triangle = match &mesh.indices {
+                Some(indices) => [indices[i], indices[i + 1], indices[i + 2]],
+                None => [i as u32, i as u32 + 1, i as u32 + 2],
+            };
+            let triangle = triangle.map(vertex);
+            let Some((distance, barycentrics)) =
+                intersect_triangle(local_origin, local_dir, triangle)
+            else {
+                continue;
+            };
+            if distance < max_dist {
+                max_dist = distance;
+                closest = Some(RayHit {
+                    distance,
+                    position: origin + dir * distance,
+                    instance: InstanceHandle(self.instance_handles.owners[index]),
+                    material: MaterialHandle(self.blas.instances[index].material_index),
+                    primitive: primitive as u32,
+                    barycentrics,
+                });
+            }
+        }
+        closest
+    }
+
+    /// Refits or rebuilds the top-level BVH if instances changed since the
+    /// last call. Returns `true` if the tree was modified.
+    pub fn update_tlas(&mut self) -> bool {
+        if !self.tlas_dirty {
+            return false;
+        }
+        self.tlas_dirty = false;
+        let bounds: Vec<Aabb> = (0..self.blas.instances.len())
+            .map(|i| self.instance_bounds(i))
+            .collect();
+        self.tlas.update(&bounds);
+        true
+    }
+
+    pub fn add_instance(
+        &mut self,
+        blas_index: u32,
+        model_to_world: glam::Mat4,
+        material: MaterialHandle,
+    ) -> InstanceHandle {
+        self.instance_handles.sync(self.blas.instances.len());
+        self.blas
+            .add_instance(blas_index, model_to_world, material.0);
+        let index = self.blas.instances.len() - 1;
+        self.instance_meshes.resize(index, u32::MAX);
+        self.instance_meshes.push(blas_index);
+        extend_range(&mut self.dirty.instances, index, index + 1);
+        self.tlas_dirty = true;
+        InstanceHandle(self.instance_handles.insert())
+    }
+
+    /// Removes an instance.
+    ///
+    /// The last instance is moved into the free spot, which invalidates
+    /// indices into `blas.instances` but not handles.
+    pub fn remove_instance(&mut self, handle: InstanceHandle) -> bool {
+        self.instance_handles.sync(self.blas.instances.len());
+        let Some(index) = self.instance_handles.swap_remove(handle.0) else {
+            return false;
+        };
+        // Instances pushed to `blas` directly have no known mesh.
+        self.instance_meshes
+            .resize(self.blas.instances.len(), u32::MAX);
+        self.blas.instances.swap_remove(index);
+        self.instance_meshes.swap_remove(index);
+        self.tlas_dirty = true;
+        // The previous last slot must be cleared on the GPU as well.
+        extend_range(
+            &mut self.dirty.instances,
+            index,
+            self.blas.instances.len() + 1,
+        );
+        true
+    }
+
+    pub fn instances(&self) -> impl Iterator<Item = InstanceHandle> + '_ {
+        self.instance_handles
+            .owners
+            .iter()
+            .map(|&handle| InstanceHandle(handle))
+    }
+
+    pub fn instance_index(&self, handle: InstanceHandle) -> Option<usize> {
+        self.instance_handles.get(handle.0)
+    }
+
+    pub fn instance(&self, handle: InstanceHandle) -> Option<&Instance> {
+        self.instance_index(handle)
+            .map(|index| &self.blas.instances[index])
+    }
+
+    /// BLAS entry referenced by the instance, `None` for instances added
+    /// to `blas` directly.
+    pub fn instance_mesh(&self, handle: InstanceHandle) -> Option<u32> {
+        self.instance_index(handle)
+            .and_then(|i| self.instance_meshes.get(i).copied())
+            .filter(|&mesh| mesh != u32::MAX)
+    }
+
+    pub fn transform(&self, handle: InstanceHandle) -> Option<glam::Mat4> {
+        self.instance(handle)
+            .map(|instance| instance.model_to_world)
+    }
+
+    pub fn set_transform(&mut self, handle: InstanceHandle, model_to_world: glam::Mat4) -> bool {
+        let Some(index) = self.instance_index(handle) else {
+            return false;
+        };
+        self.blas.instances[index].set_transform(model_to_world);
+        extend_range(&mut self.dirty.instances, index, index + 1);
+        self.tlas_dirty = true;
+        true
+    }
+
+    pub fn set_instance_material(
+        &mut self,
+        handle: InstanceHandle,
+        material: MaterialHandle,
+    ) -> bool {
+        let Some(index) = self.instance_index(handle) else {
+            return false;
+        };
+        self.blas.instances[index].material_index = material.0;
+        extend_range(&mut self.dirty.instances, index, index + 1);
+        true
+    }
+
+    pub fn add_material(&mut self, material: Material) -> MaterialHandle {
+        let index = self.materials.len();
+        self.materials.push(material);
+        extend_range(&mut self.dirty.materials, index, index + 1);
+        MaterialHandle(index as u32)
+    }
+
+    pub fn material(&self, handle: MaterialHandle) -> Option<&Material> {
+        self.materials.get(handle.0 as usize)
+    }
+
+    pub fn set_material(&mut self, handle: MaterialHandle, material: Material) -> bool {
+        let index = handle.0 as usize;
+        let Some(entry) = self.materials.get_mut(index) else {
+            return false;
+        };
+        *entry = material;
+        extend_range(&mut self.dirty.materials, index, index + 1);
+        true
+    }
+
+    pub fn add_light(&mut self, light: Light) -> LightHandle {
+        let index = self.lights.len();
+        self.lights.push(light);
+        extend_range(&mut self.dirty.lights, index, index + 1);
+        LightHandle(index as u32)
+    }
+
+    pub fn light(&self, handle: LightHandle) -> Option<&Light> {
+        self.lights.get(handle.0 as usize)
+    }
+
+    pub fn set_light(&mut self, handle: LightHandle, light: Light) -> bool {
+        let index = handle.0 as usize;
+        let Some(entry) = self.lights.get_mut(index) else {
+            return false;
+        };
+        *entry = light;
+        extend_range(&mut self.dirty.lights, index, index + 1);
+        true
+    }
+
+    pub fn dirty(&self) -> &DirtyRanges {
+        &self.dirty
+    }
+
+    /// Returns the modified ranges and resets the tracking.
+    pub fn take_dirty(&mut self) -> DirtyRanges {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Duration of the longest animation, in seconds.
+    pub fn animation_duration(&self) -> f32 {
+        self.animations
+            .iter()
+            .fold(0.0, |duration, a| f32::max(duration, a.duration))
+    }
+
+    /// Computes node world transforms and moves the instances and cameras
+    /// they own.
+    pub fn update_node_transforms(&mut self) {
+        let targeted = vec![true; self.nodes.len()];
+        self.move_node_instances(&targeted);
+    }
+
+    /// Computes node world transforms, but only moves the instances of the
+    /// `targeted` nodes and of their descendants.
+    ///
+    /// Other instances keep their transform, which might have been edited.
+    fn move_node_instances(&mut self, targeted: &[bool]) {
+        let mut moved = vec![false; self.nodes.len()];
+        let mut stack: Vec<(usize, glam::Mat4, bool)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(i, _)| (i, glam::Mat4::IDENTITY, false))
+            .collect();
+        while let Some((index, parent_world, parent_moved)) = stack.pop() {
+            let node = &mut self.nodes[index];
+            node.world = parent_world * node.local();
+            let node_moved = parent_moved || targeted[index];
+            moved[index] = node_moved;
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|&child| (child, node.world, node_moved)),
+            );
+        }
+
+        let nodes = &self.nodes;
+        for camera in &mut self.cameras {
+            if let Some(node) = camera.node.and_then(|n| nodes.get(n)) {
+                camera.world = node.world;
+            }
+        }
+
+        for i in (0..self.nodes.len()).filter(|&i| moved[i]) {
+            // Skinned vertices are already in world space.
+            let world = match self.nodes[i].skin {
+                Some(_) => glam::Mat4::IDENTITY,
+                None => self.nodes[i].world,
+            };
+            for j in 0..self.nodes[i].instances.len() {
+                let handle = self.nodes[i].instances[j];
+                if self.transform(handle) != Some(world) {
+                    self.set_transform(handle, world);
+                }
+            }
+        }
+    }
+
+    /// Evaluates every animation at `time`, in seconds.
+    ///
+    /// Only instances of the animated nodes are moved, and deformed meshes
+    /// have their BLAS re-built.
+    pub fn evaluate(&mut self, time: f32) {
+        let mut targeted = vec![false; self.nodes.len()];
+        for animation in &self.animations {
+            animation.apply(time, &mut self.nodes);
+            for channel in &animation.channels {
+                // Weights don't move the node.
+                if matches!(channel.values, ChannelValues::Weights(_)) {
+                    continue;
+                }
+                if let Some(targeted) = targeted.get_mut(channel.node) {
+                    *targeted = true;
+                }
+            }
         }
+        self.move_node_instances(&targeted);
+        self.update_node_weights();
+        self.update_deformed_meshes();
+    }
+
+    /// Sets the morph target weights of a mesh and re-builds its BLAS.
+    ///
+    /// The weights override the animated ones until
+    /// [`Self::clear_morph_weights`] is called. Returns `false` if the mesh
+    /// has no morph targets.
+    pub fn set_morph_weights(&mut self, blas_index: u32, weights: &[f32]) -> bool {
+        let Some(morphed) = self
+            .morphed_meshes
+            .iter_mut()
+            .find(|m| m.blas_index == blas_index)
+        else {
+            return false;
+        };
+        morphed.weights.clear();
+        morphed.weights.extend_from_slice(weights);
+        morphed.overridden = true;
+        if !self.deform_mesh(blas_index) {
+            return true;
+        }
+        self.rebuild_blas_in_place(blas_index) || self.rebuild_blas_from(blas_index)
+    }
+
+    /// Removes the override of [`Self::set_morph_weights`], the mesh follows
+    /// the animated weights again from the next [`Self::evaluate`] call.
+    ///
+    /// Returns `false` if the mesh has no morph targets.
+    pub fn clear_morph_weights(&mut self, blas_index: u32) -> bool {
+        let Some(morphed) = self
+            .morphed_meshes
+            .iter_mut()
+            .find(|m| m.blas_index == blas_index)
+        else {
+            return false;
+        };
+        morphed.overridden = false;
+        true
+    }
+
+    /// Copies the animated node weights into the meshes they instantiate,
+    /// except those overridden by [`Self::set_morph_weights`].
+    fn update_node_weights(&mut self) {
+        for node in &self.nodes {
+            if node.weights.is_empty() {
+                continue;
+            }
+            for &handle in &node.instances {
+                let Some(blas_index) = self.instance_mesh(handle) else {
+                    continue;
+                };
+                if let Some(morphed) = self
+                    .morphed_meshes
+                    .iter_mut()
+                    .find(|m| m.blas_index == blas_index && !m.overridden)
+                {
+                    morphed.weights.clone_from(&node.weights);
+                }
+            }
+        }
+    }
+
+    /// Applies morph targets and skins to every deformed mesh, and
+    /// re-builds the BLAS of those whose vertices changed.
+    pub fn update_deformed_meshes(&mut self) {
+        let deformed = self
+            .morphed_meshes
+            .iter()
+            .map(|m| m.blas_index)
+            .chain(self.skinned_meshes.iter().map(|m| m.blas_index));
+        // Entries whose BVH changed size are re-built with the ones after it.
+        let mut first_resized: Option<u32> = None;
+        for blas_index in deformed.collect::<Vec<_>>() {
+            if self.deform_mesh(blas_index) && !self.rebuild_blas_in_place(blas_index) {
+                first_resized = Some(first_resized.map_or(blas_index, |i| i.min(blas_index)));
+            }
+        }
+        if let Some(first) = first_resized {
+            self.rebuild_blas_from(first);
+        }
+    }
+
+    /// Writes the deformed vertices of a mesh, without re-building its BLAS.
+    ///
+    /// Morph targets are applied first, then the skin. Returns `true` if the
+    /// vertices changed.
+    fn deform_mesh(&mut self, blas_index: u32) -> bool {
+        let Some(Some(mesh)) = self.meshes.get_mut(blas_index as usize) else {
+            return false;
+        };
+        let previous = mesh.positions.clone();
+        let mut skinned = self
+            .skinned_meshes
+            .iter_mut()
+            .find(|m| m.blas_index == blas_index);
+        let morphed = self
+            .morphed_meshes
+            .iter()
+            .find(|m| m.blas_index == blas_index);
+
+        if let Some(morphed) = morphed {
+            // Skinned meshes are morphed in bind pose.
+            match skinned.as_deref_mut() {
+                Some(skinned) => morphed.morph(&mut skinned.positions, skinned.normals.as_mut()),
+                None => morphed.morph(&mut mesh.positions, mesh.normals.as_mut()),
+            }
+        }
+        if let Some(skinned) = skinned {
+            let joint_matrices = self.skins[skinned.skin].joint_matrices(&self.nodes);
+            skinned.deform(&joint_matrices, &mut mesh.positions, mesh.normals.as_mut());
+        }
+        mesh.positions != previous
     }
 }
 
@@ -185,4 +1022,368 @@ impl SceneGPU {
 
         resources
     }
+
+    /// Uploads the data modified since the last call, see [`Scene::take_dirty`].
+    ///
+    /// Returns `true` if a buffer was re-allocated, in which case the renderer
+    /// must re-create its scene bind groups with [`crate::Renderer::set_scene_resources`].
+    pub fn apply_changes(
+        &mut self,
+        scene: &mut Scene,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> bool {
+        let dirty = scene.take_dirty();
+        let mut reallocated = self.update_geometry(device, queue, &scene.blas, &dirty);
+        if let Some(range) = dirty.instances {
+            reallocated |= self.update_instances(device, queue, &scene.blas.instances, range);
+        }
+        if let Some(range) = dirty.materials {
+            reallocated |= self.update_materials(device, queue, &scene.materials, range);
+        }
+        if let Some(range) = dirty.lights {
+            reallocated |= self.update_lights(device, queue, &scene.lights, range);
+        }
+        // @todo: upload the top-level BVH once the `albedo_rtx` intersector
+        // can traverse it, it only serves CPU queries until then.
+        scene.update_tlas();
+        reallocated
+    }
+
+    /// Writes `instances[range]` into the existing instance buffer.
+    ///
+    /// The intersector iterates over the whole buffer, it is thus re-allocated
+    /// to the new count when instances are added or removed. Returns `true`
+    /// if the buffer was re-allocated.
+    pub fn update_instances(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[Instance],
+        range: Range<usize>,
+    ) -> bool {
+        if instances.is_empty() || instances.len() != self.instance_buffer.count() as usize {
+            let data = instance_buffer_data(instances);
+            self.instance_buffer = gpu::Buffer::new_storage_with_data(device, &data, None);
+            self.instance_buffer.update(queue, &data);
+            return true;
+        }
+        write_dirty(queue, &self.instance_buffer, instances, &range);
+        false
+    }
+
+    /// Writes `materials[range]` into the existing materials buffer.
+    ///
+    /// The buffer only grows, with some headroom to amortize additions.
+    /// Returns `true` if the buffer was re-allocated.
+    pub fn update_materials(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        materials: &[Material],
+        range: Range<usize>,
+    ) -> bool {
+        if materials.len() as u64 > self.materials_buffer.count() {
+            let capacity = materials.len().next_power_of_two();
+            self.materials_buffer = gpu::Buffer::new_storage(device, capacity as u64, None);
+            self.materials_buffer.update(queue, materials);
+            return true;
+        }
+        let end = range.end.min(materials.len());
+        write_range(
+            queue,
+            &self.materials_buffer,
+            range.start,
+            &materials[range.start..end],
+        );
+        false
+    }
+
+    /// Writes the modified BVH nodes, primitives and vertices into the
+    /// existing buffers.
+    ///
+    /// Buffers only grow, and are uploaded entirely when re-allocated.
+    /// Returns `true` if a buffer was re-allocated.
+    pub fn update_geometry(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        blas: &BLASArray,
+        dirty: &DirtyRanges,
+    ) -> bool {
+        let mut reallocated = false;
+        if let Some(range) = &dirty.bvh_nodes {
+            if blas.nodes.len() as u64 > self.bvh_buffer.count() {
+                let capacity = blas.nodes.len().next_power_of_two();
+                self.bvh_buffer = gpu::Buffer::new_storage(device, capacity as u64, None);
+                self.bvh_buffer.update(queue, &blas.nodes);
+                reallocated = true;
+            } else {
+                write_dirty(queue, &self.bvh_buffer, &blas.nodes, range);
+            }
+        }
+        if let Some(range) = &dirty.bvh_primitives {
+            if blas.primitives.len() as u64 > self.bvh_tri_buffer.count() {
+                let capacity = blas.primitives.len().next_power_of_two();
+                self.bvh_tri_buffer = gpu::Buffer::new_storage(device, capacity as u64, None);
+                self.bvh_tri_buffer.update(queue, &blas.primitives);
+                reallocated = true;
+            } else {
+                write_dirty(queue, &self.bvh_tri_buffer, &blas.primitives, range);
+            }
+        }
+        if let Some(range) = &dirty.vertices {
+            if blas.vertices.len() as u64 > self.vertex_buffer.count() {
+                let capacity = blas.vertices.len().next_power_of_two();
+                self.vertex_buffer = gpu::Buffer::new_storage(
+                    device,
+                    capacity as u64,
+                    Some(gpu::BufferInitDescriptor {
+                        label: None,
+                        usage: wgpu::BufferUsages::VERTEX,
+                    }),
+                );
+                self.vertex_buffer.update(queue, &blas.vertices);
+                reallocated = true;
+            } else {
+                write_dirty(queue, &self.vertex_buffer, &blas.vertices, range);
+            }
+        }
+        reallocated
+    }
+
+    /// Writes `lights[range]` into the existing light buffer.
+    ///
+    /// Unlike other buffers, lights are sampled using the buffer length. The
+    /// buffer is thus re-allocated to the exact count when lights are added.
+    /// Returns `true` if the buffer was re-allocated.
+    pub fn update_lights(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        lights: &[Light],
+        range: Range<usize>,
+    ) -> bool {
+        if lights.len() as u64 != self.light_buffer.count() {
+            self.light_buffer = gpu::Buffer::new_storage_with_data(device, lights, None);
+            self.light_buffer.update(queue, lights);
+            return true;
+        }
+        let end = range.end.min(lights.len());
+        write_range(
+            queue,
+            &self.light_buffer,
+            range.start,
+            &lights[range.start..end],
+        );
+        false
+    }
+}
+
+/// Content of the instance buffer, holding exactly the live instances.
+///
+/// Buffers can't be empty, an empty scene thus gets a single instance
+/// referencing no BVH.
+fn instance_buffer_data(instances: &[Instance]) -> Vec<Instance> {
+    if instances.is_empty() {
+        return vec![Instance {
+            bvh_root_index: INVALID_INDEX,
+            ..Default::default()
+        }];
+    }
+    instances.to_vec()
+}
+
+fn write_range<T: bytemuck::Pod>(
+    queue: &wgpu::Queue,
+    buffer: &gpu::Buffer<T>,
+    start: usize,
+    data: &[T],
+) {
+    if data.is_empty() {
+        return;
+    }
+    let offset = (start * std::mem::size_of::<T>()) as wgpu::BufferAddress;
+    queue.write_buffer(buffer.inner(), offset, bytemuck::cast_slice(data));
+}
+
+/// Writes `data[range]`, clamped to the data length.
+fn write_dirty<T: bytemuck::Pod>(
+    queue: &wgpu::Queue,
+    buffer: &gpu::Buffer<T>,
+    data: &[T],
+    range: &Range<usize>,
+) {
+    let end = range.end.min(data.len());
+    let start = range.start.min(end);
+    write_range(queue, buffer, start, &data[start..end]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene_with_instances(count: usize) -> (Scene, Vec<InstanceHandle>) {
+        let mut scene = Scene::default();
+        for _ in 1..count {
+            scene.add_instance(0, glam::Mat4::IDENTITY, MaterialHandle(0));
+        }
+        let handles: Vec<InstanceHandle> = scene.instances().collect();
+        for (i, &handle) in handles.iter().enumerate() {
+            scene.set_transform(
+                handle,
+                glam::Mat4::from_translation(glam::Vec3::X * i as f32),
+            );
+        }
+        (scene, handles)
+    }
+
+    #[test]
+    fn handles_survive_removals() {
+        let (mut scene, handles) = scene_with_instances(6);
+        let mut live = handles.clone();
+        // First, middle, then last instance.
+        for position in [0, 2, 3] {
+            let removed = live.remove(position);
+            assert!(scene.remove_instance(removed));
+            assert!(!scene.remove_instance(removed));
+            assert!(scene.instance(removed).is_none());
+            assert_eq!(scene.instance_index(removed), None);
+
+            assert_eq!(scene.blas.instances.len(), live.len());
+            for &handle in &live {
+                let i = handles.iter().position(|&h| h == handle).unwrap();
+                let expected = glam::Mat4::from_translation(glam::Vec3::X * i as f32);
+                assert_eq!(scene.transform(handle), Some(expected));
+            }
+        }
+        assert_eq!(scene.instances().collect::<Vec<_>>().len(), live.len());
+    }
+
+    #[test]
+    fn removal_marks_the_vacated_slot_dirty() {
+        let (mut scene, handles) = scene_with_instances(5);
+        scene.take_dirty();
+        assert!(scene.remove_instance(handles[1]));
+        assert_eq!(scene.take_dirty().instances, Some(1..5));
+
+        // Removing the last instance only clears its slot.
+        assert!(scene.remove_instance(handles[3]));
+        assert_eq!(scene.take_dirty().instances, Some(3..4));
+
+        scene.set_transform(handles[0], glam::Mat4::IDENTITY);
+        assert_eq!(scene.take_dirty().instances, Some(0..1));
+    }
+
+    #[test]
+    fn instance_buffer_holds_the_live_instances() {
+        let instances = vec![Instance::default(); 1025];
+        assert_eq!(instance_buffer_data(&instances).len(), 1025);
+
+        let padding = instance_buffer_data(&[]);
+        assert_eq!(padding.len(), 1);
+        assert_eq!(padding[0].bvh_root_index, INVALID_INDEX);
+    }
+
+    #[test]
+    fn raycast_sees_instances_moved_since_the_last_tlas_update() {
+        let mut scene = Scene::default();
+        let mesh = scene.add_mesh(MeshData {
+            positions: vec![
+                [-1.0, -1.0, 0.0, 1.0],
+                [1.0, -1.0, 0.0, 1.0],
+                [0.0, 1.0, 0.0, 1.0],
+            ],
+            ..Default::default()
+        });
+        let handle = scene.add_instance(mesh, glam::Mat4::IDENTITY, MaterialHandle(0));
+        let origin = glam::Vec3::new(0.0, 0.0, 5.0);
+        let dir = glam::Vec3::NEG_Z;
+
+        let hit = scene.raycast(origin, dir, f32::INFINITY).unwrap();
+        assert_eq!(hit.instance, handle);
+        assert!((hit.distance - 5.0).abs() < 1e-5);
+
+        scene.update_tlas();
+        let moved = glam::Mat4::from_translation(glam::Vec3::new(0.0, 0.0, 2.0));
+        scene.set_transform(handle, moved);
+        let hit = scene.raycast(origin, dir, f32::INFINITY).unwrap();
+        assert!((hit.distance - 3.0).abs() < 1e-5);
+        assert!(scene.raycast(origin, dir, 2.0).is_none());
+
+        scene.update_tlas();
+        let hit = scene.raycast(origin, dir, f32::INFINITY).unwrap();
+        assert!((hit.distance - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn set_morph_weights_override_animated_weights() {
+        use crate::animation::{Channel, Interpolation, MorphTarget};
+
+        let mut scene = Scene::default();
+        let positions = vec![
+            [-1.0, -1.0, 0.0, 1.0],
+            [1.0, -1.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 1.0],
+        ];
+        let mesh = scene.add_mesh(MeshData {
+            positions: positions.clone(),
+            ..Default::default()
+        });
+        let handle = scene.add_instance(mesh, glam::Mat4::IDENTITY, MaterialHandle(0));
+        scene.nodes.push(Node {
+            instances: vec![handle],
+            weights: vec![0.0],
+            ..Default::default()
+        });
+        scene.morphed_meshes.push(MorphedMesh {
+            blas_index: mesh,
+            targets: vec![MorphTarget {
+                positions: Some(vec![glam::Vec3::Z; 3]),
+                normals: None,
+            }],
+            weights: vec![0.0],
+            overridden: false,
+            positions,
+            normals: None,
+        });
+        scene.animations.push(Animation {
+            name: None,
+            channels: vec![Channel {
+                node: 0,
+                interpolation: Interpolation::Linear,
+                times: vec![0.0, 1.0],
+                values: ChannelValues::Weights(vec![0.0, 0.0]),
+            }],
+            duration: 1.0,
+        });
+        let depth = |scene: &Scene| scene.mesh(mesh).unwrap().positions[0][2];
+
+        assert!(scene.set_morph_weights(mesh, &[1.0]));
+        assert_eq!(depth(&scene), 1.0);
+        scene.evaluate(0.5);
+        assert_eq!(scene.morphed_meshes[0].weights, vec![1.0]);
+        assert_eq!(depth(&scene), 1.0);
+
+        assert!(scene.clear_morph_weights(mesh));
+        scene.evaluate(0.5);
+        assert_eq!(scene.morphed_meshes[0].weights, vec![0.0]);
+        assert_eq!(depth(&scene), 0.0);
+        assert!(!scene.set_morph_weights(mesh + 1, &[1.0]));
+    }
+
+    #[test]
+    fn remove_instance_keeps_meshes_paired() {
+        let mut scene = Scene::default();
+        let first = scene.instances().next().unwrap();
+        let added = scene.add_instance(0, glam::Mat4::IDENTITY, MaterialHandle(0));
+        // Pushed after the last addition, without a known mesh.
+        scene.blas.add_instance(0, glam::Mat4::IDENTITY, 0);
+
+        assert!(scene.remove_instance(first));
+        let pushed = scene.instances().find(|&h| h != added).unwrap();
+        assert_eq!(scene.instance_mesh(added), Some(0));
+        assert_eq!(scene.instance_mesh(pushed), None);
+        assert_eq!(scene.instance_meshes.len(), scene.blas.instances.len());
+    }
 }
@@ -0,0 +1,462 @@
+use glam::{Mat4, Vec3};
+
+/// Axis-aligned bounding box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub const EMPTY: Aabb = Aabb {
+        min: Vec3::splat(f32::INFINITY),
+        max: Vec3::splat(f32::NEG_INFINITY),
+    };
+    /// Used for geometry with unknown bounds, always traversed.
+    pub const INFINITE: Aabb = Aabb {
+        min: Vec3::splat(f32::NEG_INFINITY),
+        max: Vec3::splat(f32::INFINITY),
+    };
+
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+        points.into_iter().fold(Aabb::EMPTY, |mut aabb, p| {
+            aabb.grow(p);
+            aabb
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn grow(&mut self, point: Vec3) {
+        self.min = self.min.min(point);
+        self.max = self.max.max(point);
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn extent(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let e = self.extent();
+        2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+    }
+
+    /// Bounds of the box once transformed, computed from its eight corners.
+    pub fn transform(&self, matrix: &Mat4) -> Aabb {
+        if self.is_empty() || !self.min.is_finite() || !self.max.is_finite() {
+            return *self;
+        }
+        Aabb::from_points((0..8).map(|i| {
+            let corner = Vec3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+            matrix.transform_point3(corner)
+        }))
+    }
+
+    /// Slab test, returns the entry and exit distances along the ray.
+    pub fn intersect(&self, origin: Vec3, inv_dir: Vec3, max_dist: f32) -> Option<(f32, f32)> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut near = 0.0_f32;
+        let mut far = max_dist;
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - origin[axis]) * inv_dir[axis];
+            let t1 = (self.max[axis] - origin[axis]) * inv_dir[axis];
+            let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+            // A ray parallel to the slab, starting on one of its planes, gives
+            // `0 * inf = NaN`. The comparisons below are false for NaN, the
+            // axis thus doesn't constrain the ray.
+            if t0 > near {
+                near = t0;
+            }
+            if t1 < far {
+                far = t1;
+            }
+        }
+        if near <= far {
+            Some((near, far))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Aabb::EMPTY
+    }
+}
+
+/// Node of the top-level BVH.
+///
+/// Internal nodes store the index of their left child, the right child
+/// directly follows it. Leaves store a range in [`TLAS::indices`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TLASNode {
+    pub min: [f32; 3],
+    pub left_or_first: u32,
+    pub max: [f32; 3],
+    pub count: u32,
+}
+
+impl TLASNode {
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb {
+            min: self.min.into(),
+            max: self.max.into(),
+        }
+    }
+
+    fn set_aabb(&mut self, aabb: &Aabb) {
+        self.min = aabb.min.into();
+        self.max = aabb.max.into();
+    }
+}
+
+const MAX_LEAF_SIZE: usize = 2;
+
+/// Refitting degrades the tree. Past this growth of the root surface area
+/// compared to the last build, the tree is rebuilt instead.
+const REBUILD_AREA_RATIO: f32 = 2.0;
+
+/// Top-level BVH over the scene instances.
+///
+/// Built and traversed on the CPU, for picking and other scene queries.
+/// When only transforms change, the tree is refit which is linear in the
+/// number of instances, and rebuilt when its quality degrades too much.
+///
+/// The tree isn't uploaded: the intersector of `albedo_rtx` has no top-level
+/// traversal and still tests every instance, so rendering cost remains
+/// linear in the number of instances.
+#[derive(Default)]
+pub struct TLAS {
+    pub nodes: Vec<TLASNode>,
+    /// Instance indices, referenced by leaves.
+    pub indices: Vec<u32>,
+    built_area: f32,
+}
+
+impl TLAS {
+    pub fn new(bounds: &[Aabb]) -> Self {
+        let mut tlas = TLAS::default();
+        tlas.rebuild(bounds);
+        tlas
+    }
+
+    pub fn root(&self) -> Option<&TLASNode> {
+        self.nodes.first()
+    }
+
+    pub fn rebuild(&mut self, bounds: &[Aabb]) {
+        self.nodes.clear();
+        self.indices = (0..bounds.len() as u32).collect();
+        if bounds.is_empty() {
+            self.built_area = 0.0;
+            return;
+        }
+        self.nodes.reserve(bounds.len() * 2);
+        self.nodes.push(TLASNode::default());
+        self.subdivide(0, 0, bounds.len(), bounds);
+        self.built_area = self.nodes[0].aabb().surface_area();
+    }
+
+    /// Updates node bounds without changing the topology.
+    ///
+    /// `bounds` must contain as many entries as when the tree was built.
+    pub fn refit(&mut self, bounds: &[Aabb]) {
+        // Children are always stored after their parent.
+        for i in (0..self.nodes.len()).rev() {
+            let node = self.nodes[i];
+            let aabb = if node.is_leaf() {
+                let first = node.left_or_first as usize;
+                self.indices[first..first + node.count as usize]
+                    .iter()
+                    .fold(Aabb::EMPTY, |aabb, &index| {
+                        aabb.union(&bounds[index as usize])
+                    })
+            } else {
+                let left = node.left_or_first as usize;
+                self.nodes[left].aabb().union(&self.nodes[left + 1].aabb())
+            };
+            self.nodes[i].set_aabb(&aabb);
+        }
+    }
+
+    /// Refits the tree, or rebuilds it if instances were added or removed
+    /// or if the refit tree became too loose.
+    ///
+    /// Returns `true` if the tree was rebuilt.
+    pub fn update(&mut self, bounds: &[Aabb]) -> bool {
+        if bounds.len() != self.indices.len() {
+            self.rebuild(bounds);
+            return true;
+        }
+        self.refit(bounds);
+        let area = self.root().map(|n| n.aabb().surface_area()).unwrap_or(0.0);
+        if area.is_finite() && area > self.built_area * REBUILD_AREA_RATIO {
+            self.rebuild(bounds);
+            return true;
+        }
+        false
+    }
+
+    /// Visits the instances whose bounds are hit by the ray, front to back.
+    ///
+    /// `visit` returns the closest hit distance found so far, used to cull
+    /// the remaining nodes.
+    pub fn traverse<F: FnMut(u32, f32) -> f32>(
+        &self,
+        origin: Vec3,
+        dir: Vec3,
+        max_dist: f32,
+        mut visit: F,
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let inv_dir = dir.recip();
+        let mut closest = max_dist;
+        let mut stack: Vec<(u32, f32)> = Vec::with_capacity(64);
+        stack.push((0, 0.0));
+        while let Some((index, near)) = stack.pop() {
+            if near > closest {
+                continue;
+            }
+            let node = &self.nodes[index as usize];
+            if node.aabb().intersect(origin, inv_dir, closest).is_none() {
+                continue;
+            }
+            if node.is_leaf() {
+                let first = node.left_or_first as usize;
+                for &instance in &self.indices[first..first + node.count as usize] {
+                    closest = closest.min(visit(instance, closest));
+                }
+                continue;
+            }
+            let left = node.left_or_first;
+            let hit_left = self.nodes[left as usize]
+                .aabb()
+                .intersect(origin, inv_dir, closest);
+            let hit_right = self.nodes[left as usize + 1]
+                .aabb()
+                .intersect(origin, inv_dir, closest);
+            match (hit_left, hit_right) {
+                (Some((l, _)), Some((r, _))) => {
+                    // Push the farthest first to process the closest next.
+                    if l <= r {
+                        stack.push((left + 1, r));
+                        stack.push((left, l));
+                    } else {
+                        stack.push((left, l));
+                        stack.push((left + 1, r));
+                    }
+                }
+                (Some((l, _)), None) => stack.push((left, l)),
+                (None, Some((r, _))) => stack.push((left + 1, r)),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn subdivide(&mut self, node: usize, first: usize, count: usize, bounds: &[Aabb]) {
+        let aabb = self.indices[first..first + count]
+            .iter()
+            .fold(Aabb::EMPTY, |aabb, &i| aabb.union(&bounds[i as usize]));
+        self.nodes[node].set_aabb(&aabb);
+
+        if count <= MAX_LEAF_SIZE {
+            self.nodes[node].left_or_first = first as u32;
+            self.nodes[node].count = count as u32;
+            return;
+        }
+
+        // Median split along the largest axis of the centroids.
+        let centroids =
+            self.indices[first..first + count]
+                .iter()
+                .fold(Aabb::EMPTY, |mut aabb, &i| {
+                    aabb.grow(finite_center(&bounds[i as usize]));
+                    aabb
+                });
+        let axis = largest_axis(centroids.extent());
+        let half = count / 2;
+        self.indices[first..first + count].select_nth_unstable_by(half, |&a, &b| {
+            let a = finite_center(&bounds[a as usize])[axis];
+            let b = finite_center(&bounds[b as usize])[axis];
+            a.total_cmp(&b)
+        });
+
+        let left = self.nodes.len();
+        self.nodes.push(TLASNode::default());
+        self.nodes.push(TLASNode::default());
+        self.nodes[node].left_or_first = left as u32;
+        self.nodes[node].count = 0;
+        self.subdivide(left, first, half, bounds);
+        self.subdivide(left + 1, first + half, count - half, bounds);
+    }
+}
+
+fn largest_axis(v: Vec3) -> usize {
+    if v.x >= v.y && v.x >= v.z {
+        0
+    } else if v.y >= v.z {
+        1
+    } else {
+        2
+    }
+}
+
+fn finite_center(aabb: &Aabb) -> Vec3 {
+    let center = aabb.center();
+    if center.is_finite() {
+        center
+    } else {
+        Vec3::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(center: Vec3) -> Aabb {
+        Aabb {
+            min: center - Vec3::splat(0.5),
+            max: center + Vec3::splat(0.5),
+        }
+    }
+
+    /// Closest instance hit by the ray, using the bounds as geometry.
+    fn raycast(tlas: &TLAS, bounds: &[Aabb], origin: Vec3, dir: Vec3) -> Option<(u32, f32)> {
+        let mut closest = None;
+        tlas.traverse(origin, dir, f32::INFINITY, |index, max_dist| {
+            match bounds[index as usize].intersect(origin, dir.recip(), max_dist) {
+                Some((near, _)) => {
+                    closest = Some((index, near));
+                    near
+                }
+                None => max_dist,
+            }
+        });
+        closest
+    }
+
+    fn row(offset: f32) -> Vec<Aabb> {
+        (0..16)
+            .map(|i| cube(Vec3::new(i as f32 * 2.0 + offset, 0.0, 0.0)))
+            .collect()
+    }
+
+    #[test]
+    fn intersect_rejects_empty_box() {
+        let hit = Aabb::EMPTY.intersect(Vec3::ZERO, Vec3::ONE, f32::INFINITY);
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn intersect_parallel_ray_on_slab() {
+        let aabb = cube(Vec3::splat(0.5));
+        // Starts on the `x = 0` plane, with a null x direction.
+        let dir = Vec3::Z;
+        let hit = aabb.intersect(Vec3::new(0.0, 0.5, -1.0), dir.recip(), f32::INFINITY);
+        assert_eq!(hit, Some((1.0, 2.0)));
+        let hit = aabb.intersect(Vec3::new(1.0, 0.5, -1.0), dir.recip(), f32::INFINITY);
+        assert_eq!(hit, Some((1.0, 2.0)));
+        let hit = aabb.intersect(Vec3::new(2.0, 0.5, -1.0), dir.recip(), f32::INFINITY);
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn intersect_respects_max_distance() {
+        let aabb = cube(Vec3::new(0.0, 0.0, 5.0));
+        let dir = Vec3::Z.recip();
+        assert_eq!(aabb.intersect(Vec3::ZERO, dir, 4.0), None);
+        assert_eq!(aabb.intersect(Vec3::ZERO, dir, 10.0), Some((4.5, 5.5)));
+    }
+
+    #[test]
+    fn refit_matches_rebuild() {
+        let mut refit = TLAS::new(&row(0.0));
+        let moved = row(0.25);
+        refit.refit(&moved);
+        let rebuilt = TLAS::new(&moved);
+        assert_eq!(refit.root().unwrap().aabb(), rebuilt.root().unwrap().aabb());
+
+        // Every internal node encloses its children.
+        for node in refit.nodes.iter().filter(|n| !n.is_leaf()) {
+            let left = node.left_or_first as usize;
+            let children = refit.nodes[left]
+                .aabb()
+                .union(&refit.nodes[left + 1].aabb());
+            assert_eq!(node.aabb(), children);
+        }
+
+        for i in 0..16 {
+            let origin = Vec3::new(i as f32 * 2.0 + 0.25, 0.0, -10.0);
+            let expected = raycast(&rebuilt, &moved, origin, Vec3::Z);
+            assert_eq!(raycast(&refit, &moved, origin, Vec3::Z), expected);
+            assert_eq!(expected, Some((i, 9.5)));
+        }
+    }
+
+    #[test]
+    fn update_rebuilds_loose_or_resized_trees() {
+        let mut tlas = TLAS::new(&row(0.0));
+        assert!(!tlas.update(&row(0.1)));
+
+        let mut scattered = row(0.0);
+        scattered[3] = cube(Vec3::new(0.0, 500.0, 0.0));
+        assert!(tlas.update(&scattered));
+
+        let mut grown = row(0.0);
+        grown.push(cube(Vec3::new(0.0, 4.0, 0.0)));
+        assert!(tlas.update(&grown));
+        assert_eq!(tlas.indices.len(), grown.len());
+    }
+
+    #[test]
+    fn raycast_returns_closest_instance() {
+        let bounds: Vec<Aabb> = [8.0, 2.0, 5.0]
+            .iter()
+            .map(|&z| cube(Vec3::new(0.0, 0.0, z)))
+            .collect();
+        let tlas = TLAS::new(&bounds);
+        assert_eq!(raycast(&tlas, &bounds, Vec3::ZERO, Vec3::Z), Some((1, 1.5)));
+        assert_eq!(
+            raycast(&tlas, &bounds, Vec3::new(0.0, 0.0, 10.0), -Vec3::Z),
+            Some((0, 1.5))
+        );
+        assert_eq!(raycast(&tlas, &bounds, Vec3::X * 3.0, Vec3::Z), None);
+    }
+
+    #[test]
+    fn empty_tree_is_never_hit() {
+        let tlas = TLAS::new(&[]);
+        assert!(tlas.root().is_none());
+        assert_eq!(raycast(&tlas, &[], Vec3::ZERO, Vec3::Z), None);
+    }
+}
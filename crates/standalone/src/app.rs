@@ -7,7 +7,7 @@ use std::{
 use image::GenericImageView;
 use loupiote_core::{
     loaders::{self},
-    BlitMode, Device, ProbeGPU, Renderer, Scene, SceneGPU,
+    BlitMode, Device, PanoramaDescriptor, PanoramaLayout, ProbeGPU, Renderer, Scene, SceneGPU,
 };
 use winit::{
     application::ApplicationHandler,
@@ -15,14 +15,16 @@ use winit::{
 };
 
 use crate::{
-    camera::{CameraController, CameraMoveCommand},
+    camera::{CameraController, CameraMode, CameraMoveCommand},
     commands,
     errors::Error,
     event::LoadEvent,
+    exr_layers,
     gui::{GUIContext, GUI},
     input_manager::InputManager,
     logger::log,
-    Event, Settings, Spawner,
+    settings::SequenceFormat,
+    CameraPose, Event, Project, Settings, Spawner,
 };
 
 pub struct Plaftorm {
@@ -54,10 +56,19 @@ pub struct ApplicationContext {
 
     pub last_time: Instant,
     pub event_captured: bool,
+    pub cursor_position: Option<winit::dpi::PhysicalPosition<f64>>,
+    /// Cursor position when the left button got pressed over the viewport.
+    pub click_position: Option<winit::dpi::PhysicalPosition<f64>>,
+    /// Frames rendered since the camera last moved.
+    pub still_frames: u32,
+    pub project: Project,
 
     pub shader_paths: PathBuf,
 }
 
+/// Still frames after which the full resolution scale is used again.
+const RESOLUTION_RESTORE_FRAMES: u32 = 8;
+
 impl ApplicationContext {
     pub fn init(&mut self) {
         self.settings.blit_mode = BlitMode::DenoisedPathrace;
@@ -72,9 +83,64 @@ impl ApplicationContext {
             commands::EditorCommand::ToggleAccumulation => {
                 self.settings.accumulate = !self.settings.accumulate
             }
+            commands::EditorCommand::FocusUnderCursor => self.focus_under_cursor(),
+            commands::EditorCommand::ToggleCameraMode => {
+                self.settings.camera_mode = match self.settings.camera_mode {
+                    CameraMode::Fly => CameraMode::Orbit,
+                    CameraMode::Orbit => CameraMode::Fly,
+                }
+            }
+            commands::EditorCommand::FrameScene => self.frame_scene(),
+        }
+    }
+
+    /// Sets the focus distance to the surface under the cursor.
+    pub fn focus_under_cursor(&mut self) {
+        let Some(cursor) = self.cursor_position else {
+            return;
+        };
+        let (x, y) = self.render_position(cursor);
+        if let Some(hit) = self.renderer.pick(&self.scene, x, y) {
+            // Focus distance is measured along the view axis.
+            let offset = hit.position - self.camera_controller.origin;
+            self.settings.camera.focus_distance = offset.dot(self.camera_controller.direction);
         }
     }
 
+    /// Selects the instance under the cursor, unless the cursor moved more
+    /// than a few pixels since `press_position`, i.e., the camera rotated.
+    fn select_under_cursor(&mut self, press_position: winit::dpi::PhysicalPosition<f64>) {
+        const CLICK_DISTANCE: f64 = 4.0;
+        let Some(cursor) = self.cursor_position else {
+            return;
+        };
+        let (dx, dy) = (cursor.x - press_position.x, cursor.y - press_position.y);
+        if dx * dx + dy * dy > CLICK_DISTANCE * CLICK_DISTANCE {
+            return;
+        }
+        let (x, y) = self.render_position(cursor);
+        let hit = self.renderer.pick(&self.scene, x, y);
+        self.gui.windows.outliner_window.selected = hit.map(|hit| hit.instance);
+    }
+
+    /// Converts a window position to a pixel position of the render, which
+    /// might be downscaled.
+    fn render_position(&self, position: winit::dpi::PhysicalPosition<f64>) -> (f32, f32) {
+        let window = self.platform.window.inner_size();
+        let render = self.renderer.get_size();
+        (
+            position.x as f32 * render.0 as f32 / window.width.max(1) as f32,
+            position.y as f32 * render.1 as f32 / window.height.max(1) as f32,
+        )
+    }
+
+    /// Moves the camera back along its view axis until the whole scene is
+    /// visible.
+    pub fn frame_scene(&mut self) {
+        self.camera_controller
+            .frame(&self.scene.bounds(), self.settings.camera.v_fov);
+    }
+
     pub fn resize(&mut self, width_target: u32, height_target: u32) {
         let limits = &self.platform.device.inner().limits();
         let max_bytes_per_pixel = Renderer::max_ssbo_element_in_bytes();
@@ -113,6 +179,15 @@ impl ApplicationContext {
         );
     }
 
+    pub fn set_resolution_scale(&mut self, scale: f32) {
+        self.renderer.set_resolution_scale(
+            &self.platform.device,
+            &self.scene_gpu,
+            self.probe.as_ref(),
+            scale,
+        );
+    }
+
     pub fn load_blue_noise<P: AsRef<path::Path>>(&mut self, path: P) {
         // @todo: Remove unwrap.
         let img = image::io::Reader::open(path).unwrap().decode().unwrap();
@@ -136,6 +211,15 @@ impl ApplicationContext {
         self.load_env(&bytes[..]);
     }
 
+    pub fn load_lut(&mut self, name: String, data: &[u8]) -> Result<(), Error> {
+        let text = String::from_utf8_lossy(data);
+        let lut = loupiote_core::Lut::parse(&text)?;
+        self.renderer
+            .set_lut(&self.platform.device, &self.platform.queue, Some(&lut));
+        self.settings.lut = Some(lut.title.unwrap_or(name));
+        Ok(())
+    }
+
     pub fn load_env(&mut self, data: &[u8]) {
         let decoder = image::codecs::hdr::HdrDecoder::new(data).unwrap();
         let metadata = decoder.metadata();
@@ -166,10 +250,176 @@ impl ApplicationContext {
         log!("Loading GLB...");
         let mut scene = Scene::default();
         loaders::load_gltf(data, &mut scene)?;
-        self.upload_scene(scene)
+        self.upload_scene(scene)?;
+        if !self.scene.cameras.is_empty() {
+            self.select_camera(0);
+        }
+        Ok(())
+    }
+
+    pub fn select_camera(&mut self, index: usize) {
+        if let Some(camera) = self.scene.cameras.get(index) {
+            self.camera_controller
+                .look_to(camera.origin(), camera.direction());
+            self.settings.camera = camera.params();
+        }
+    }
+
+    pub fn camera_pose(&self) -> CameraPose {
+        CameraPose {
+            origin: self.camera_controller.origin,
+            direction: self.camera_controller.direction,
+            v_fov: self.settings.camera.v_fov,
+        }
+    }
+
+    pub fn set_camera_pose(&mut self, pose: &CameraPose) {
+        self.camera_controller.look_to(pose.origin, pose.direction);
+        self.settings.camera.v_fov = pose.v_fov;
+    }
+
+    pub fn go_to_bookmark(&mut self, index: usize) {
+        if let Some(pose) = self.project.bookmarks.get(index).map(|b| b.pose) {
+            self.set_camera_pose(&pose);
+        }
+    }
+
+    pub fn load_project<P: AsRef<path::Path>>(&mut self, path: P) -> Result<(), Error> {
+        self.project = Project::load(path)?;
+        let window = &mut self.gui.windows.camera_path_window;
+        window.time = 0.0;
+        window.playing = false;
+        Ok(())
+    }
+
+    /// Renders every frame of `Settings::sequence`, moving the camera along
+    /// the project camera path and evaluating the scene animations.
+    ///
+    /// Each frame restarts accumulation and is written as
+    /// `frame_<index>.<extension>` in `directory`.
+    pub fn export_sequence<P: AsRef<path::Path>>(&mut self, directory: P) {
+        let sequence = self.settings.sequence;
+        let previous_pose = self.camera_pose();
+        let previous_mode = self.renderer.blit_mode();
+        let previous_scale = self.renderer.resolution_scale();
+        self.renderer.set_blit_mode(BlitMode::Pahtrace);
+        self.set_resolution_scale(1.0);
+
+        let frame_count = sequence.frame_count();
+        for frame in 0..frame_count {
+            let time = sequence.start + frame as f32 / sequence.fps;
+            if let Some(pose) = self.project.camera_path.sample(time) {
+                self.set_camera_pose(&pose);
+            }
+            if self.scene.animation_duration() > 0.0 {
+                self.scene.evaluate(time);
+            }
+            self.apply_scene_changes();
+
+            let path = directory.as_ref().join(format!(
+                "frame_{:04}.{}",
+                frame,
+                sequence.format.extension()
+            ));
+            match self.render_sequence_frame(&path) {
+                Ok(samples) => {
+                    log!("Frame {}/{}: {} samples", frame + 1, frame_count, samples);
+                }
+                Err(e) => {
+                    self.gui.set_error(e);
+                    break;
+                }
+            }
+        }
+
+        self.renderer.set_blit_mode(previous_mode);
+        self.set_resolution_scale(previous_scale);
+        self.set_camera_pose(&previous_pose);
+        // Forces the timeline to re-evaluate the scene at its own time.
+        self.gui.windows.timeline_window.changed = true;
+    }
+
+    fn render_sequence_frame(&mut self, path: &path::Path) -> Result<u32, Error> {
+        let device = &self.platform.device;
+        let queue = &self.platform.queue;
+        let samples = pollster::block_on(self.renderer.converge(
+            device,
+            queue,
+            &self.camera_controller.view_transform(),
+            &self.settings.camera,
+            &self.settings.sequence.convergence,
+        ))?;
+        let (width, height) = *self.renderer.get_size();
+        let saved = match self.settings.sequence.format {
+            SequenceFormat::Png => {
+                let pixels = pollster::block_on(self.renderer.read_pixels(device.inner(), queue))?;
+                image::RgbaImage::from_raw(width, height, pixels).map(|output| output.save(path))
+            }
+            SequenceFormat::Exr => {
+                let mut radiance =
+                    pollster::block_on(self.renderer.read_radiance(device.inner(), queue))?;
+                for pixel in radiance.chunks_exact_mut(4) {
+                    pixel[3] = 1.0;
+                }
+                image::Rgba32FImage::from_raw(width, height, radiance)
+                    .map(|output| output.save(path))
+            }
+            SequenceFormat::LayeredExr => {
+                self.save_layered_exr(path)?;
+                return Ok(samples);
+            }
+        };
+        match saved {
+            Some(Ok(())) => Ok(samples),
+            Some(Err(e)) => Err(Error::ImageSave(e.to_string())),
+            None => Err(Error::TextureToBufferReadFail),
+        }
     }
 
-    pub fn save_screenshot<P: AsRef<path::Path>>(&self, path: P) {
+    /// Updates the histogram window with the last auto-exposure histogram
+    /// read back, without waiting for the GPU.
+    fn poll_luminance_histogram(&mut self) {
+        let window = &mut self.gui.windows.histogram_window;
+        if !window.open || self.settings.tonemapping.auto_exposure.is_none() {
+            window.histogram = None;
+            return;
+        }
+        let histogram = self
+            .renderer
+            .poll_luminance_histogram(self.platform.device.inner(), &self.platform.queue);
+        match histogram {
+            None => {}
+            Some(Ok(histogram)) => window.histogram = Some(histogram),
+            Some(Err(e)) => {
+                window.histogram = None;
+                log!("Failed to read luminance histogram: {:?}", e);
+            }
+        }
+    }
+
+    /// Writes the beauty and the auxiliary passes of the current render in
+    /// a single EXR file.
+    fn save_layered_exr(&self, path: &path::Path) -> Result<(), Error> {
+        let device = self.platform.device.inner();
+        let queue = &self.platform.queue;
+        let passes = exr_layers::LAYERS
+            .iter()
+            .map(|(aov, _, _)| pollster::block_on(self.renderer.read_aov(device, queue, *aov)))
+            .collect::<Result<Vec<_>, _>>()?;
+        exr_layers::save(path, *self.renderer.get_size(), &passes)
+    }
+
+    pub fn save_screenshot<P: AsRef<path::Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exr"))
+        {
+            if let Err(e) = self.save_layered_exr(path) {
+                self.gui.set_error(e);
+            }
+            return;
+        }
         // @todo: Doesn't work anymore because executed async.
         let size = self.renderer.get_size();
         // @todo: handle error.
@@ -186,6 +436,39 @@ impl ApplicationContext {
         }
     }
 
+    pub fn save_panorama<P: AsRef<path::Path>>(&mut self, path: P, layout: PanoramaLayout) {
+        const PANORAMA_SIZE: u32 = 1024;
+        const PANORAMA_SAMPLES: u32 = 64;
+
+        let descriptor = PanoramaDescriptor {
+            view_transform: self.camera_controller.view_transform(),
+            camera: self.settings.camera,
+            layout,
+            size: PANORAMA_SIZE,
+            samples: PANORAMA_SAMPLES,
+        };
+        let image = pollster::block_on(self.renderer.render_panorama(
+            &self.platform.device,
+            &self.platform.queue,
+            &self.scene_gpu,
+            self.probe.as_ref(),
+            &descriptor,
+        ));
+        let Ok(image) = image else {
+            self.gui.set_error("failed to render panorama");
+            return;
+        };
+        if let Some(output) = image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(
+            image.width(),
+            image.height(),
+            image.data(),
+        ) {
+            if let Err(e) = output.save(path) {
+                self.gui.set_error(Error::ImageSave(e.to_string()));
+            }
+        }
+    }
+
     pub fn width(&self) -> u32 {
         self.renderer.get_size().0
     }
@@ -212,7 +495,27 @@ impl ApplicationContext {
         self.renderer.reload_shaders(&self.platform.device);
     }
 
-    pub fn upload_scene(&mut self, scene: Scene) -> Result<(), Error> {
+    /// Uploads edits made through the [`Scene`] API since the last frame.
+    pub fn apply_scene_changes(&mut self) {
+        if self.scene.dirty().is_empty() {
+            return;
+        }
+        let reallocated = self.scene_gpu.apply_changes(
+            &mut self.scene,
+            self.platform.device.inner(),
+            &self.platform.queue,
+        );
+        if reallocated {
+            self.renderer.set_scene_resources(
+                &self.platform.device,
+                &self.scene_gpu,
+                self.probe.as_ref(),
+            );
+        }
+        self.renderer.reset_accumulation(&self.platform.queue);
+    }
+
+    pub fn upload_scene(&mut self, mut scene: Scene) -> Result<(), Error> {
         log!(
             "Scene: {{\n\tMeshes={:?}\n\tVertices = {:?}\n\tCWBVH Nodes = {:?}\n\tCWBVH Primitives = {:?}\n\tInstances = {:?}\n}}",
             scene.blas.entries.len(),
@@ -222,6 +525,9 @@ impl ApplicationContext {
             scene.blas.instances.len(),
         );
 
+        // Everything is uploaded below, pending edits are thus irrelevant.
+        scene.take_dirty();
+        scene.update_tlas();
         self.scene = scene;
         self.scene_gpu = SceneGPU::new_from_scene(
             &self.scene,
@@ -244,6 +550,11 @@ impl ApplicationContext {
             .windows
             .scene_info_window
             .set_bvh_nodes_count(self.scene.blas.nodes.len());
+        self.gui.windows.materials_window.clear_textures();
+        self.gui
+            .windows
+            .timeline_window
+            .set_duration(self.scene.animation_duration());
 
         self.renderer
             .set_resources(&self.platform.device, &self.scene_gpu, self.probe.as_ref());
@@ -294,7 +605,31 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
                     .create_view(&wgpu::TextureViewDescriptor::default());
                 let timestamp_period = self.platform.queue.get_timestamp_period();
 
+                let mut camera_moved = false;
+                let path_window = &mut self.gui.windows.camera_path_window;
+                if path_window.advance(delta, self.project.camera_path.duration()) {
+                    if let Some(pose) = self.project.camera_path.sample(path_window.time) {
+                        self.set_camera_pose(&pose);
+                        camera_moved = true;
+                    }
+                }
+                self.camera_controller.set_mode(self.settings.camera_mode);
                 let view_transform = self.camera_controller.update(delta);
+                // Resizing re-allocates every render target, the full
+                // resolution is thus only restored once the camera settled.
+                if camera_moved || self.camera_controller.is_moving() {
+                    self.still_frames = 0;
+                } else {
+                    self.still_frames = self.still_frames.saturating_add(1);
+                }
+                let scale_moving = self.still_frames < RESOLUTION_RESTORE_FRAMES;
+                camera_moved |= !self.camera_controller.is_static();
+                let timeline = &mut self.gui.windows.timeline_window;
+                if timeline.advance(delta) && !self.scene.animations.is_empty() {
+                    self.scene.evaluate(timeline.time);
+                }
+                self.apply_scene_changes();
+                self.set_resolution_scale(self.settings.resolution_scale(scale_moving));
 
                 let mut encoder = self
                     .platform
@@ -302,18 +637,36 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
                     .inner()
                     .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
+                let camera_pose = self.camera_pose();
                 let renderer = &mut self.renderer;
                 renderer.queries.start_frame(timestamp_period);
 
-                if !self.settings.accumulate || !self.camera_controller.is_static() {
+                if !self.settings.accumulate || camera_moved {
                     renderer.reset_accumulation(&self.platform.queue);
                 }
 
                 // TODO: Can be done only on change
                 renderer.use_noise_texture(&self.platform.queue, self.settings.use_blue_noise);
                 renderer.set_blit_mode(self.settings.blit_mode);
+                renderer.set_bounces(&self.platform.queue, self.settings.bounces);
+                renderer.set_tonemapping(&self.platform.queue, self.settings.tonemapping);
+                renderer.set_temporal_upscaling(
+                    &self.platform.device,
+                    self.settings.temporal_upscaling,
+                );
 
-                renderer.raytrace(&mut encoder, &self.platform.queue, &view_transform);
+                renderer.raytrace(
+                    &mut encoder,
+                    &self.platform.queue,
+                    &view_transform,
+                    &self.settings.camera,
+                );
+                renderer.adapt_exposure(
+                    &self.platform.device,
+                    &mut encoder,
+                    &self.platform.queue,
+                    delta,
+                );
                 renderer.blit(&self.platform.device, &mut encoder, &view);
                 renderer.accumulate = true;
 
@@ -328,9 +681,19 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
                         event_loop_proxy: &self.event_loop_proxy,
                         renderer: renderer,
                         settings: &mut self.settings,
+                        scene: &self.scene,
+                        project: &mut self.project,
+                        camera_pose,
+                        view_transform,
                     },
                     &view,
                 );
+                if let Some((handle, transform)) = self.gui.windows.outliner_window.edited.take() {
+                    self.scene.set_transform(handle, transform);
+                }
+                if let Some((handle, material)) = self.gui.windows.materials_window.edited.take() {
+                    self.scene.set_material(handle, material);
+                }
 
                 self.platform
                     .queue
@@ -340,6 +703,8 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
 
                 renderer.queries.end_frame(timestamp_period);
 
+                self.poll_luminance_histogram();
+
                 self.platform.window.request_redraw();
             }
             winit::event::WindowEvent::Resized(size) => {
@@ -405,10 +770,34 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
                     self.run_command(cmd);
                 }
             }
+            winit::event::WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some(position);
+            }
             winit::event::WindowEvent::MouseInput { button, state, .. } => {
-                if button == winit::event::MouseButton::Left {
-                    self.camera_controller.rotation_enabled =
-                        state == winit::event::ElementState::Pressed;
+                let pressed = state == winit::event::ElementState::Pressed;
+                match button {
+                    winit::event::MouseButton::Left => {
+                        self.camera_controller.rotation_enabled = pressed;
+                        if pressed {
+                            self.click_position =
+                                self.cursor_position.filter(|_| !self.event_captured);
+                        } else if let Some(position) = self.click_position.take() {
+                            self.select_under_cursor(position);
+                        }
+                    }
+                    winit::event::MouseButton::Right | winit::event::MouseButton::Middle => {
+                        self.camera_controller.pan_enabled = pressed;
+                    }
+                    _ => {}
+                }
+            }
+            winit::event::WindowEvent::MouseWheel { delta, .. } => {
+                if !self.event_captured {
+                    let amount = match delta {
+                        winit::event::MouseScrollDelta::LineDelta(_, y) => y,
+                        winit::event::MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 50.0,
+                    };
+                    self.camera_controller.dolly(amount * 2.0);
                 }
             }
             _ => {}
@@ -426,13 +815,33 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
     fn user_event(&mut self, _: &winit::event_loop::ActiveEventLoop, event: crate::Event) {
         match event {
             Event::SaveScreenshot(path) => self.save_screenshot(path),
+            Event::SavePanorama(path, layout) => self.save_panorama(path, layout),
             Event::ReloadShaders => self.reload_shaders(),
+            Event::SelectCamera(index) => self.select_camera(index),
+            Event::FrameScene => self.frame_scene(),
+            Event::GoToBookmark(index) => self.go_to_bookmark(index),
+            Event::SaveProject(path) => self
+                .project
+                .save(path)
+                .unwrap_or_else(|e| self.gui.set_error(e)),
+            Event::ExportSequence(directory) => self.export_sequence(directory),
+            Event::LoadProject(path) => self
+                .load_project(path)
+                .unwrap_or_else(|e| self.gui.set_error(e)),
             Event::Load(load) => match load {
                 LoadEvent::GLTF(data) => self
                     .load_file(&data[..])
                     .unwrap_or_else(|_| self.gui.set_error("failed to load gltf")),
                 LoadEvent::Env(data) => self.load_env(&data[..]),
+                LoadEvent::Lut(name, data) => self
+                    .load_lut(name, &data[..])
+                    .unwrap_or_else(|e| self.gui.set_error(e)),
             },
+            Event::ClearLut => {
+                self.renderer
+                    .set_lut(&self.platform.device, &self.platform.queue, None);
+                self.settings.lut = None;
+            }
         }
     }
 
@@ -445,10 +854,10 @@ impl ApplicationHandler<crate::Event> for ApplicationContext {
         match event {
             winit::event::DeviceEvent::MouseMotion { delta } => {
                 if !self.event_captured {
-                    self.camera_controller.rotate(
-                        (delta.0 / (self.width() as f64 * 0.5)) as f32,
-                        (delta.1 / (self.height() as f64 * 0.5)) as f32,
-                    );
+                    let x = (delta.0 / (self.width() as f64 * 0.5)) as f32;
+                    let y = (delta.1 / (self.height() as f64 * 0.5)) as f32;
+                    self.camera_controller.rotate(x, y);
+                    self.camera_controller.pan(x, y);
                 }
             }
             _ => {}
@@ -9,6 +9,16 @@ pub enum CameraMoveCommand {
     Right = 0b0001_0000,
 }
 
+/// Navigation style of the [`CameraController`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CameraMode {
+    /// First-person navigation, rotating around the eye.
+    #[default]
+    Fly,
+    /// Turntable navigation, rotating around [`CameraController::target`].
+    Orbit,
+}
+
 #[derive(Default)]
 pub struct CameraController {
     pub move_speed_factor: f32,
@@ -21,8 +31,15 @@ pub struct CameraController {
     pub direction: glam::Vec3,
     pub commands: enumflags2::BitFlags<CameraMoveCommand>,
 
+    pub mode: CameraMode,
+    /// Point the camera orbits around, in world space.
+    pub target: glam::Vec3,
+    pub pan_velocity: glam::Vec2,
+    pub dolly_velocity: f32,
+
     pub rotation_enabled: bool,
     pub translation_enabled: bool,
+    pub pan_enabled: bool,
 }
 
 impl CameraController {
@@ -46,6 +63,50 @@ impl CameraController {
         return def;
     }
 
+    /// Moves the camera to `origin`, looking along `direction`, and stops
+    /// any ongoing motion.
+    pub fn look_to(&mut self, origin: glam::Vec3, direction: glam::Vec3) {
+        self.origin = origin;
+        self.direction = direction;
+        self.move_velocity = glam::Vec3::ZERO;
+        self.rot_velocity = glam::Vec2::ZERO;
+        self.pan_velocity = glam::Vec2::ZERO;
+        self.dolly_velocity = 0.0;
+        self.target = origin + direction * self.target_distance();
+    }
+
+    /// Switches between fly and orbit navigation.
+    ///
+    /// Entering orbit mode keeps the current target, moved onto the view axis.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if self.mode == mode {
+            return;
+        }
+        self.mode = mode;
+        self.target = self.origin + self.direction * self.target_distance();
+    }
+
+    /// Moves the camera back along its view axis until `bounds` fit in a
+    /// vertical field of view of `v_fov` radians, and orbits around its center.
+    pub fn frame(&mut self, bounds: &loupiote_core::Aabb, v_fov: f32) {
+        if bounds.is_empty() || !bounds.extent().is_finite() {
+            return;
+        }
+        let radius = (bounds.extent().length() * 0.5).max(0.001);
+        let distance = radius / (v_fov * 0.5).sin().max(0.001);
+        self.target = bounds.center();
+        self.look_to(self.target - self.direction * distance, self.direction);
+    }
+
+    fn target_distance(&self) -> f32 {
+        let distance = (self.target - self.origin).dot(self.direction);
+        if distance > 0.001 {
+            distance
+        } else {
+            5.0
+        }
+    }
+
     pub fn rotate(&mut self, x: f32, y: f32) {
         if self.rotation_enabled {
             self.rot_velocity.x += x;
@@ -53,6 +114,20 @@ impl CameraController {
         }
     }
 
+    pub fn pan(&mut self, x: f32, y: f32) {
+        if self.pan_enabled && self.mode == CameraMode::Orbit {
+            self.pan_velocity.x += x;
+            self.pan_velocity.y += y;
+        }
+    }
+
+    /// Moves towards the orbit target, or along the view axis in fly mode.
+    ///
+    /// Positive `amount` moves closer.
+    pub fn dolly(&mut self, amount: f32) {
+        self.dolly_velocity += amount;
+    }
+
     pub fn set_command(&mut self, cmd: CameraMoveCommand) {
         if self.translation_enabled {
             self.commands.insert(enumflags2::BitFlags::from(cmd));
@@ -64,8 +139,12 @@ impl CameraController {
     }
 
     pub fn update(&mut self, delta: f32) -> glam::Mat4 {
+        if self.mode == CameraMode::Orbit {
+            return self.update_orbit(delta);
+        }
+
         let mut right = self.direction.cross(glam::Vec3::Y).normalize();
-        let mut up = right.cross(self.direction).normalize();
+        let up = right.cross(self.direction).normalize();
 
         let rot_velocity = self.rot_velocity * self.rot_speed_factor * delta;
         let rot = glam::Quat::from_axis_angle(up, -rot_velocity.x)
@@ -73,7 +152,6 @@ impl CameraController {
 
         self.direction = (rot * self.direction).normalize();
         right = self.direction.cross(glam::Vec3::Y).normalize();
-        up = right.cross(self.direction).normalize();
 
         if self.commands.contains(CameraMoveCommand::Left) {
             self.move_velocity.x += -1.0;
@@ -89,15 +167,55 @@ impl CameraController {
         }
         let move_velocity = self.move_velocity * self.move_speed_factor * delta;
 
-        let move_force = right * move_velocity.x + self.direction * move_velocity.z;
+        let move_force = right * move_velocity.x
+            + self.direction * (move_velocity.z + self.dolly_velocity * delta);
         self.origin += move_force;
 
+        self.damp();
+        self.view_transform()
+    }
+
+    fn update_orbit(&mut self, delta: f32) -> glam::Mat4 {
+        let right = self.direction.cross(glam::Vec3::Y).normalize();
+        let up = right.cross(self.direction).normalize();
+        let mut distance = (self.target - self.origin).length().max(0.001);
+
+        // Panning moves the target in the view plane, proportionally to the
+        // distance so that the scene follows the cursor.
+        let pan_velocity = self.pan_velocity * self.move_speed_factor * delta * distance;
+        self.target += right * -pan_velocity.x + up * pan_velocity.y;
+
+        // Turntable: yaw around the world up axis, pitch around the camera right
+        // axis without going over the poles.
+        let rot_velocity = self.rot_velocity * self.rot_speed_factor * delta;
+        let pitch = self.direction.y.clamp(-1.0, 1.0).asin();
+        let max_pitch = 89_f32.to_radians();
+        let pitch_delta = (pitch - rot_velocity.y).clamp(-max_pitch, max_pitch) - pitch;
+        let rot = glam::Quat::from_axis_angle(glam::Vec3::Y, -rot_velocity.x)
+            * glam::Quat::from_axis_angle(right, pitch_delta);
+        self.direction = (rot * self.direction).normalize();
+
+        distance = (distance * (1.0 - self.dolly_velocity * delta).clamp(0.1, 10.0)).max(0.001);
+        self.origin = self.target - self.direction * distance;
+
+        self.damp();
+        self.view_transform()
+    }
+
+    fn damp(&mut self) {
         let move_damping = (1.0 - self.move_damping_factor).clamp(0.0, 1.0);
         let rot_damping = (1.0 - self.rot_damping_factor).clamp(0.0, 1.0);
 
         self.rot_velocity = self.rot_velocity * rot_damping;
         self.move_velocity = self.move_velocity * move_damping;
+        self.pan_velocity *= move_damping;
+        self.dolly_velocity *= move_damping;
+    }
 
+    /// Camera to world transform, looking down +Z.
+    pub fn view_transform(&self) -> glam::Mat4 {
+        let right = self.direction.cross(glam::Vec3::Y).normalize();
+        let up = right.cross(self.direction).normalize();
         let translation = glam::Mat4::from_translation(self.origin);
         let rot = glam::Mat4::from_cols(
             right.extend(0.0),
@@ -106,12 +224,19 @@ impl CameraController {
             glam::Vec4::W,
         );
         translation * rot
-        // return (right, up);
     }
 
     pub fn is_static(&self) -> bool {
-        !self.rotation_enabled
-            && self.rot_velocity.length_squared() < 0.00000001
-            && self.move_velocity.length_squared() < 0.00000001
+        // Panning is only possible in orbit mode.
+        let panning = self.pan_enabled && self.mode == CameraMode::Orbit;
+        !(self.rotation_enabled || panning || self.is_moving())
+    }
+
+    /// Unlike [`Self::is_static`], held buttons alone don't count as motion.
+    pub fn is_moving(&self) -> bool {
+        self.rot_velocity.length_squared() >= 0.00000001
+            || self.move_velocity.length_squared() >= 0.00000001
+            || self.pan_velocity.length_squared() >= 0.00000001
+            || self.dolly_velocity.abs() >= 0.0001
     }
 }
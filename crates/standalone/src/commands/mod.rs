@@ -1,3 +1,6 @@
 pub enum EditorCommand {
     ToggleAccumulation,
+    FocusUnderCursor,
+    ToggleCameraMode,
+    FrameScene,
 }
@@ -3,8 +3,14 @@ use loupiote_core;
 #[derive(Debug)]
 pub enum Error {
     FileNotFound(String),
+    FileWrite(String),
     TextureToBufferReadFail,
+    BufferReadFail,
     AccelBuild(String),
+    InvalidProject(String),
+    ImageSave(String),
+    InvalidLut(String),
+    AovUnavailable(String),
 }
 
 impl From<loupiote_core::Error> for Error {
@@ -12,7 +18,10 @@ impl From<loupiote_core::Error> for Error {
         match e {
             loupiote_core::Error::FileNotFound(f) => Error::FileNotFound(f),
             loupiote_core::Error::TextureToBufferReadFail => Error::TextureToBufferReadFail,
+            loupiote_core::Error::BufferReadFail => Error::BufferReadFail,
             loupiote_core::Error::AccelBuild(reason) => Error::AccelBuild(reason),
+            loupiote_core::Error::InvalidLut(reason) => Error::InvalidLut(reason),
+            loupiote_core::Error::AovUnavailable(reason) => Error::AovUnavailable(reason),
         }
     }
 }
@@ -23,10 +32,16 @@ impl From<Error> for String {
             Error::FileNotFound(filename) => {
                 format!("file not found: {}", filename)
             }
+            Error::FileWrite(reason) => format!("failed to write file: {}", reason),
             Error::TextureToBufferReadFail => String::from("failed to read pixels from GPU to CPU"),
+            Error::BufferReadFail => String::from("failed to read buffer from GPU to CPU"),
             Error::AccelBuild(reason) => {
                 format!("failed to build acceleration structure: {:?}", reason)
             }
+            Error::InvalidProject(reason) => format!("invalid project file: {}", reason),
+            Error::ImageSave(reason) => format!("failed to save image: {}", reason),
+            Error::InvalidLut(reason) => format!("invalid LUT: {}", reason),
+            Error::AovUnavailable(reason) => format!("AOV unavailable: {}", reason),
         }
     }
 }
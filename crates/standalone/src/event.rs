@@ -1,14 +1,31 @@
 use std::path;
 
+use loupiote_core::PanoramaLayout;
+
 pub enum LoadEvent {
     GLTF(Vec<u8>),
     Env(Vec<u8>),
+    /// `.cube` grading LUT, with its file name.
+    Lut(String, Vec<u8>),
 }
 
 pub enum Event {
     SaveScreenshot(path::PathBuf),
+    SavePanorama(path::PathBuf, PanoramaLayout),
     Load(LoadEvent),
     ReloadShaders,
+    /// Jumps to the camera at the given index in `Scene::cameras`.
+    SelectCamera(usize),
+    /// Moves the camera to fit the scene bounds.
+    FrameScene,
+    /// Moves the camera to the bookmark at the given index in `Project::bookmarks`.
+    GoToBookmark(usize),
+    SaveProject(path::PathBuf),
+    LoadProject(path::PathBuf),
+    /// Renders `Settings::sequence` into the given directory.
+    ExportSequence(path::PathBuf),
+    /// Disables the grading LUT.
+    ClearLut,
 }
 
 pub type EventLoopProxy = winit::event_loop::EventLoopProxy<Event>;
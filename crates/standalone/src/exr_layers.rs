@@ -0,0 +1,73 @@
+use std::path;
+
+use exr::prelude::{
+    AnyChannel, AnyChannels, AttributeValue, Encoding, FlatSamples, Image, ImageAttributes,
+    IntegerBounds, Layer, LayerAttributes, Text, WritableImage,
+};
+use loupiote_core::Aov;
+
+use crate::errors::Error;
+
+/// Passes written by [`save`], with their layer and channel names.
+pub const LAYERS: [(Aov, &str, &[&str]); 5] = [
+    (Aov::Beauty, "beauty", &["R", "G", "B"]),
+    (Aov::Albedo, "albedo", &["R", "G", "B"]),
+    (Aov::ShadingNormal, "normal", &["X", "Y", "Z"]),
+    (Aov::Depth, "depth", &["Z"]),
+    (Aov::Motion, "motion", &["X", "Y"]),
+];
+
+/// Units of the layer, stored in its `units` attribute.
+fn units(aov: Aov) -> Option<&'static str> {
+    match aov {
+        Aov::Depth => Some("world units"),
+        Aov::Motion => Some("pixels, current minus previous position, y down"),
+        _ => None,
+    }
+}
+
+/// Writes one EXR layer per entry of [`LAYERS`].
+///
+/// `passes` holds the interleaved floats read back for each layer, in order.
+pub fn save(path: &path::Path, size: (u32, u32), passes: &[Vec<f32>]) -> Result<(), Error> {
+    let resolution = (size.0 as usize, size.1 as usize);
+    let layers: Vec<_> = LAYERS
+        .iter()
+        .zip(passes)
+        .map(|((aov, name, channels), pass)| {
+            let channels = channels
+                .iter()
+                .enumerate()
+                .map(|(index, channel)| {
+                    let samples = pass
+                        .iter()
+                        .skip(index)
+                        .step_by(channels.len())
+                        .copied()
+                        .collect();
+                    AnyChannel::new(*channel, FlatSamples::F32(samples))
+                })
+                .collect::<Vec<_>>();
+            let mut attributes = LayerAttributes::named(*name);
+            if let Some(units) = units(*aov) {
+                attributes
+                    .other
+                    .insert(Text::from("units"), AttributeValue::Text(Text::from(units)));
+            }
+            Layer::new(
+                resolution,
+                attributes,
+                Encoding::SMALL_LOSSLESS,
+                AnyChannels::sort(channels.into()),
+            )
+        })
+        .collect();
+
+    Image::from_layers(
+        ImageAttributes::new(IntegerBounds::from_dimensions(resolution)),
+        layers,
+    )
+    .write()
+    .to_file(path)
+    .map_err(|e| Error::ImageSave(e.to_string()))
+}
@@ -1,3 +1,4 @@
+use crate::camera::CameraMode;
 use crate::{Event, LoadEvent};
 
 mod toolbar;
@@ -7,6 +8,12 @@ mod windows;
 pub struct Windows {
     pub scene_info_window: windows::SceneInfoWindow,
     pub performance_info_window: windows::PerformanceInfoWindow,
+    pub timeline_window: windows::TimelineWindow,
+    pub camera_path_window: windows::CameraPathWindow,
+    pub sequence_window: windows::SequenceWindow,
+    pub histogram_window: windows::HistogramWindow,
+    pub outliner_window: windows::OutlinerWindow,
+    pub materials_window: windows::MaterialsWindow,
 }
 
 pub struct GUIContext<'a> {
@@ -15,6 +22,12 @@ pub struct GUIContext<'a> {
     pub event_loop_proxy: &'a crate::EventLoopProxy,
     pub renderer: &'a mut crate::Renderer,
     pub settings: &'a mut crate::Settings,
+    pub scene: &'a loupiote_core::Scene,
+    pub project: &'a mut crate::Project,
+    /// Pose of the camera controller, used to add bookmarks and keyframes.
+    pub camera_pose: crate::CameraPose,
+    /// View to world transform of the camera controller.
+    pub view_transform: glam::Mat4,
 }
 
 pub struct GUI {
@@ -52,6 +65,12 @@ impl GUI {
                     open: false,
                     ..Default::default()
                 },
+                timeline_window: windows::TimelineWindow::default(),
+                camera_path_window: windows::CameraPathWindow::default(),
+                sequence_window: windows::SequenceWindow::default(),
+                histogram_window: windows::HistogramWindow::default(),
+                outliner_window: windows::OutlinerWindow::default(),
+                materials_window: windows::MaterialsWindow::default(),
             },
         }
     }
@@ -100,6 +119,12 @@ impl GUI {
         render_menu_bar(ctx, context, windows);
         windows.scene_info_window.render(ctx);
         windows.performance_info_window.render(&context, ctx);
+        windows.timeline_window.render(ctx);
+        windows.camera_path_window.render(context, ctx);
+        windows.sequence_window.render(context, ctx);
+        windows.histogram_window.render(ctx);
+        windows.outliner_window.render(context, ctx);
+        windows.materials_window.render(context, ctx);
 
         let pixels_per_point = context.platform.window.scale_factor() as f32;
         let screen_descriptor = egui_wgpu::ScreenDescriptor {
@@ -188,13 +213,40 @@ fn render_menu_bar(egui_ctx: &egui::Context, context: &mut GUIContext, windows:
                     windows.scene_info_window.open = true;
                     ui.close_menu();
                 }
+                if ui.button("Outliner").clicked() {
+                    windows.outliner_window.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Materials").clicked() {
+                    windows.materials_window.open = true;
+                    ui.close_menu();
+                }
                 if ui.button("Performance Information").clicked() {
                     windows.performance_info_window.open = true;
                     ui.close_menu();
                 }
+                if ui.button("Timeline").clicked() {
+                    windows.timeline_window.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Camera Path").clicked() {
+                    windows.camera_path_window.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Sequence Export").clicked() {
+                    windows.sequence_window.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Luminance Histogram").clicked() {
+                    windows.histogram_window.open = true;
+                    ui.close_menu();
+                }
             });
+            render_cameras_menu(ui, context);
             toolbar::render_toolbar_gui(ui, context.settings);
+            render_grading_menu(ui, context);
             render_screenshot_menu(ui, context);
+            render_panorama_menu(ui, context);
         });
     });
 }
@@ -222,6 +274,149 @@ fn render_file_menu(ui: &mut egui::Ui, context: &GUIContext) {
                 }
             });
         }
+        // @todo: support wasm.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            if ui.button("Open Project").clicked() {
+                ui.close_menu();
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("project", &["loupiote"])
+                    .set_parent(context.platform.window.as_ref())
+                    .pick_file();
+                let event_loop_proxy = context.event_loop_proxy.clone();
+                context.executor.spawn_local(async move {
+                    if let Some(file) = dialog.await {
+                        event_loop_proxy
+                            .send_event(Event::LoadProject(file.path().to_path_buf()))
+                            .ok();
+                    }
+                });
+            }
+            if ui.button("Save Project").clicked() {
+                ui.close_menu();
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("project", &["loupiote"])
+                    .set_parent(context.platform.window.as_ref())
+                    .save_file();
+                let event_loop_proxy = context.event_loop_proxy.clone();
+                context.executor.spawn_local(async move {
+                    if let Some(file) = dialog.await {
+                        event_loop_proxy
+                            .send_event(Event::SaveProject(file.path().to_path_buf()))
+                            .ok();
+                    }
+                });
+            }
+        }
+    });
+}
+
+fn render_cameras_menu(ui: &mut egui::Ui, context: &mut GUIContext) {
+    ui.menu_button("Cameras", |ui| {
+        if context.scene.cameras.is_empty() {
+            ui.label("No camera");
+        }
+        for (i, camera) in context.scene.cameras.iter().enumerate() {
+            let name = match &camera.name {
+                Some(name) => name.clone(),
+                None => format!("Camera {}", i),
+            };
+            if ui.button(name).clicked() {
+                context
+                    .event_loop_proxy
+                    .send_event(Event::SelectCamera(i))
+                    .ok();
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let mode = &mut context.settings.camera_mode;
+            ui.radio_value(mode, CameraMode::Fly, "Fly");
+            ui.radio_value(mode, CameraMode::Orbit, "Orbit");
+        })
+        .response
+        .on_hover_text("Press O to toggle");
+        if ui
+            .button("Frame Scene")
+            .on_hover_text("Press Home to frame the scene")
+            .clicked()
+        {
+            context.event_loop_proxy.send_event(Event::FrameScene).ok();
+            ui.close_menu();
+        }
+    });
+}
+
+fn render_grading_menu(ui: &mut egui::Ui, context: &GUIContext) {
+    ui.menu_button("Grading", |ui| {
+        match &context.settings.lut {
+            Some(name) => ui.label(format!("LUT: {}", name)),
+            None => ui.label("No LUT"),
+        };
+        if ui.button("Load LUT").clicked() {
+            ui.close_menu();
+            let dialog = rfd::AsyncFileDialog::new()
+                .add_filter("LUT", &["cube"])
+                .set_parent(context.platform.window.as_ref())
+                .pick_file();
+            let event_loop_proxy = context.event_loop_proxy.clone();
+            context.executor.spawn_local(async move {
+                if let Some(file) = dialog.await {
+                    let data = file.read().await;
+                    event_loop_proxy
+                        .send_event(Event::Load(LoadEvent::Lut(file.file_name(), data)))
+                        .ok();
+                }
+            });
+        }
+        if ui
+            .add_enabled(
+                context.settings.lut.is_some(),
+                egui::Button::new("Clear LUT"),
+            )
+            .clicked()
+        {
+            ui.close_menu();
+            context.event_loop_proxy.send_event(Event::ClearLut).ok();
+        }
+    });
+}
+
+fn render_panorama_menu(ui: &mut egui::Ui, context: &GUIContext) {
+    // @todo: support wasm.
+    #[cfg(not(target_arch = "wasm32"))]
+    ui.menu_button("VR", |ui| {
+        let layouts = [
+            (
+                "Export Stereo Panorama",
+                loupiote_core::PanoramaLayout::OmniStereoTopBottom { ipd: 0.064 },
+            ),
+            (
+                "Export Cubemap",
+                loupiote_core::PanoramaLayout::CubemapStrip,
+            ),
+        ];
+        for (label, layout) in layouts {
+            if !ui.button(label).clicked() {
+                continue;
+            }
+            ui.close_menu();
+            let dialog = rfd::AsyncFileDialog::new()
+                .add_filter("image", &["png", "jpg"])
+                .set_parent(&context.platform.window)
+                .save_file();
+            let event_loop_proxy = context.event_loop_proxy.clone();
+            context.executor.spawn_local(async move {
+                if let Some(file) = dialog.await {
+                    event_loop_proxy
+                        .send_event(Event::SavePanorama(file.path().to_path_buf(), layout))
+                        .ok();
+                }
+            });
+        }
     });
 }
 
@@ -231,6 +426,7 @@ fn render_screenshot_menu(ui: &mut egui::Ui, context: &GUIContext) {
     if ui.button("📷").clicked() {
         let dialog = rfd::AsyncFileDialog::new()
             .add_filter("image", &["png", "jpg"])
+            .add_filter("multi-layer exr", &["exr"])
             .set_parent(&context.platform.window)
             .save_file();
         let event_loop_proxy = context.event_loop_proxy.clone();
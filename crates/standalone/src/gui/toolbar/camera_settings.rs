@@ -0,0 +1,123 @@
+use loupiote_core::Projection;
+
+fn projection_name(projection: &Projection) -> &'static str {
+    match projection {
+        Projection::Perspective => "Perspective",
+        Projection::Orthographic { .. } => "Orthographic",
+        Projection::Equirectangular => "Equirectangular",
+        Projection::Fisheye { .. } => "Fisheye",
+        Projection::OmniStereo { .. } => "Omni Stereo",
+    }
+}
+
+fn render_projection_gui(ui: &mut egui::Ui, projection: &mut Projection) {
+    let choices = [
+        Projection::Perspective,
+        Projection::Orthographic { height: 10.0 },
+        Projection::Equirectangular,
+        Projection::Fisheye {
+            fov: 180_f32.to_radians(),
+        },
+        Projection::OmniStereo { eye_offset: -0.032 },
+    ];
+    egui::ComboBox::from_label("Projection")
+        .selected_text(projection_name(projection))
+        .show_ui(ui, |ui| {
+            for choice in choices {
+                let selected = projection_name(projection) == projection_name(&choice);
+                if ui
+                    .selectable_label(selected, projection_name(&choice))
+                    .clicked()
+                    && !selected
+                {
+                    *projection = choice;
+                }
+            }
+        });
+    match projection {
+        Projection::Orthographic { height } => {
+            ui.add(
+                egui::DragValue::new(height)
+                    .speed(0.1)
+                    .range(0.001..=f32::MAX)
+                    .prefix("Height: "),
+            );
+        }
+        Projection::Fisheye { fov } => {
+            ui.drag_angle(fov);
+        }
+        Projection::OmniStereo { eye_offset } => {
+            ui.add(
+                egui::DragValue::new(eye_offset)
+                    .speed(0.001)
+                    .prefix("Eye Offset: "),
+            );
+        }
+        _ => {}
+    }
+}
+
+pub fn render_camera_toolbar_gui(ui: &mut egui::Ui, settings: &mut crate::Settings) {
+    let camera = &mut settings.camera;
+
+    render_projection_gui(ui, &mut camera.projection);
+
+    let mut v_fov = camera.v_fov.to_degrees();
+    let slider = egui::Slider::new(&mut v_fov, 1.0..=170.0)
+        .suffix("°")
+        .text("Vertical FOV");
+    if ui.add(slider).changed() {
+        camera.v_fov = v_fov.to_radians();
+    }
+
+    let mut match_viewport = camera.aspect_ratio.is_none();
+    if ui
+        .checkbox(&mut match_viewport, "Viewport Aspect")
+        .changed()
+    {
+        camera.aspect_ratio = if match_viewport {
+            None
+        } else {
+            Some(16.0 / 9.0)
+        };
+    }
+    if let Some(aspect_ratio) = camera.aspect_ratio.as_mut() {
+        ui.add(
+            egui::DragValue::new(aspect_ratio)
+                .speed(0.01)
+                .range(0.1..=10.0)
+                .prefix("Aspect: "),
+        );
+    }
+
+    let far = camera.far;
+    ui.add(
+        egui::DragValue::new(&mut camera.near)
+            .speed(0.01)
+            .range(0.0001..=far)
+            .prefix("Near: "),
+    );
+    let near = camera.near;
+    ui.add(
+        egui::DragValue::new(&mut camera.far)
+            .speed(1.0)
+            .range(near..=f32::MAX)
+            .prefix("Far: "),
+    );
+
+    ui.separator();
+    ui.add(
+        egui::DragValue::new(&mut camera.aperture)
+            .speed(0.001)
+            .range(0.0..=10.0)
+            .prefix("Aperture: "),
+    );
+    ui.add(
+        egui::DragValue::new(&mut camera.focus_distance)
+            .speed(0.01)
+            .range(0.001..=f32::MAX)
+            .prefix("Focus Distance: "),
+    )
+    .on_hover_text("Press F to focus on the surface under the cursor");
+    ui.add(egui::Slider::new(&mut camera.blades, 0..=12).text("Blades"));
+}
@@ -1,4 +1,6 @@
-use loupiote_core::BlitMode;
+use loupiote_core::{
+    AutoExposure, BlitMode, Bloom, BounceSettings, ToneMapping, TonemapSettings, MAX_BOUNCES,
+};
 
 pub fn render_settings_toolbar_gui(ui: &mut egui::Ui, settings: &mut crate::Settings) {
     ui.checkbox(&mut settings.accumulate, "Accumulate");
@@ -20,4 +22,98 @@ pub fn render_settings_toolbar_gui(ui: &mut egui::Ui, settings: &mut crate::Sett
                 "Motion Vectors",
             );
         });
+    ui.separator();
+    render_resolution_gui(ui, settings);
+    ui.separator();
+    render_bounces_gui(ui, &mut settings.bounces);
+    ui.separator();
+    render_tonemapping_gui(ui, &mut settings.tonemapping);
+}
+
+fn render_resolution_gui(ui: &mut egui::Ui, settings: &mut crate::Settings) {
+    ui.add(egui::Slider::new(&mut settings.resolution_scale, 0.1..=1.0).text("Resolution Scale"));
+    let mut dynamic = settings.moving_resolution_scale.is_some();
+    if ui.checkbox(&mut dynamic, "Lower While Moving").changed() {
+        // Moves at the current scale, and converges at full resolution.
+        settings.moving_resolution_scale = dynamic.then_some(settings.resolution_scale);
+        if dynamic {
+            settings.resolution_scale = 1.0;
+        }
+    }
+    if let Some(scale) = settings.moving_resolution_scale.as_mut() {
+        ui.add(egui::Slider::new(scale, 0.1..=1.0).text("Moving Scale"));
+    }
+    ui.checkbox(&mut settings.temporal_upscaling, "Temporal Upscaling")
+        .on_hover_text("Only used by the denoised and temporal blit modes");
+}
+
+fn render_bounces_gui(ui: &mut egui::Ui, bounces: &mut BounceSettings) {
+    ui.add(egui::Slider::new(&mut bounces.moving, 1..=MAX_BOUNCES).text("Bounces (Moving)"));
+    ui.add(
+        egui::Slider::new(&mut bounces.converging, 1..=MAX_BOUNCES).text("Bounces (Converging)"),
+    );
+    let mut roulette = bounces.russian_roulette.is_some();
+    if ui.checkbox(&mut roulette, "Russian Roulette").changed() {
+        bounces.russian_roulette = if roulette { Some(3) } else { None };
+    }
+    if let Some(start) = bounces.russian_roulette.as_mut() {
+        ui.add(egui::Slider::new(start, 1..=MAX_BOUNCES).text("Roulette Start"));
+    }
+}
+
+fn render_tonemapping_gui(ui: &mut egui::Ui, tonemapping: &mut TonemapSettings) {
+    ui.add(egui::Slider::new(&mut tonemapping.exposure, -10.0..=10.0).text("Exposure (EV)"));
+    let mut auto_exposure = tonemapping.auto_exposure.is_some();
+    if ui.checkbox(&mut auto_exposure, "Auto Exposure").changed() {
+        tonemapping.auto_exposure = auto_exposure.then(AutoExposure::default);
+    }
+    if let Some(auto_exposure) = tonemapping.auto_exposure.as_mut() {
+        render_auto_exposure_gui(ui, auto_exposure);
+    }
+    let mut bloom = tonemapping.bloom.is_some();
+    if ui.checkbox(&mut bloom, "Bloom").changed() {
+        tonemapping.bloom = bloom.then(Bloom::default);
+    }
+    if let Some(bloom) = tonemapping.bloom.as_mut() {
+        render_bloom_gui(ui, bloom);
+    }
+    egui::ComboBox::from_label("Tone Mapping")
+        .selected_text(format!("{:?}", tonemapping.tone_mapping))
+        .show_ui(ui, |ui| {
+            let value = &mut tonemapping.tone_mapping;
+            ui.selectable_value(value, ToneMapping::Clamp, "Clamp");
+            ui.selectable_value(value, ToneMapping::Reinhard, "Reinhard");
+            ui.selectable_value(value, ToneMapping::AcesFilmic, "ACES Filmic");
+            ui.selectable_value(value, ToneMapping::AgX, "AgX");
+        });
+    let mut custom_gamma = tonemapping.gamma.is_some();
+    if ui.checkbox(&mut custom_gamma, "Custom Gamma").changed() {
+        tonemapping.gamma = custom_gamma.then_some(2.2);
+    }
+    if let Some(gamma) = tonemapping.gamma.as_mut() {
+        ui.add(egui::Slider::new(gamma, 1.0..=3.0).text("Gamma"));
+    }
+}
+
+fn render_auto_exposure_gui(ui: &mut egui::Ui, auto_exposure: &mut AutoExposure) {
+    ui.add(egui::Slider::new(&mut auto_exposure.speed, 0.1..=10.0).text("Adaptation Speed"));
+    ui.add(
+        egui::Slider::new(&mut auto_exposure.min_log_luminance, -16.0..=0.0)
+            .text("Min Luminance (log2)"),
+    );
+    ui.add(
+        egui::Slider::new(&mut auto_exposure.max_log_luminance, 0.0..=16.0)
+            .text("Max Luminance (log2)"),
+    );
+    ui.add(egui::Slider::new(&mut auto_exposure.low_percentile, 0.0..=1.0).text("Low Percentile"));
+    ui.add(
+        egui::Slider::new(&mut auto_exposure.high_percentile, 0.0..=1.0).text("High Percentile"),
+    );
+}
+
+fn render_bloom_gui(ui: &mut egui::Ui, bloom: &mut Bloom) {
+    ui.add(egui::Slider::new(&mut bloom.threshold, 0.0..=10.0).text("Bloom Threshold"));
+    ui.add(egui::Slider::new(&mut bloom.knee, 0.0..=1.0).text("Bloom Knee"));
+    ui.add(egui::Slider::new(&mut bloom.intensity, 0.0..=1.0).text("Bloom Intensity"));
+    ui.add(egui::Slider::new(&mut bloom.radius, 0.0..=1.0).text("Bloom Radius"));
 }
@@ -0,0 +1,128 @@
+use crate::gui::{views, GUIContext};
+use crate::Event;
+
+#[derive(Default)]
+pub struct CameraPathWindow {
+    pub open: bool,
+    pub playing: bool,
+    /// Current time along the camera path, in seconds.
+    pub time: f32,
+    /// Set when the user moved the cursor, reset by the application.
+    pub changed: bool,
+    bookmark_name: String,
+    keyframe_time: f32,
+}
+
+impl CameraPathWindow {
+    /// Advances the time by `delta` seconds when playing, looping at `duration`.
+    ///
+    /// Returns `true` if the camera must be moved along the path.
+    pub fn advance(&mut self, delta: f32, duration: f32) -> bool {
+        let changed = std::mem::take(&mut self.changed);
+        if !self.playing || duration <= 0.0 {
+            return changed;
+        }
+        self.time = (self.time + delta) % duration;
+        true
+    }
+
+    pub fn render(&mut self, context: &mut GUIContext, egui_ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Camera Path")
+            .resizable(true)
+            .open(&mut open)
+            .show(egui_ctx, |ui| {
+                self.render_bookmarks(ui, context);
+                ui.separator();
+                self.render_path(ui, context);
+            });
+        self.open = open;
+    }
+
+    fn render_bookmarks(&mut self, ui: &mut egui::Ui, context: &mut GUIContext) {
+        ui.heading("Bookmarks");
+        let mut removed = None;
+        for (i, bookmark) in context.project.bookmarks.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button(&bookmark.name).clicked() {
+                    context
+                        .event_loop_proxy
+                        .send_event(Event::GoToBookmark(i))
+                        .ok();
+                }
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            context.project.bookmarks.remove(i);
+        }
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.bookmark_name);
+            if ui.button("Add").clicked() {
+                let name = if self.bookmark_name.is_empty() {
+                    format!("Bookmark {}", context.project.bookmarks.len())
+                } else {
+                    std::mem::take(&mut self.bookmark_name)
+                };
+                context.project.bookmarks.push(crate::CameraBookmark {
+                    name,
+                    pose: context.camera_pose,
+                });
+            }
+        });
+    }
+
+    fn render_path(&mut self, ui: &mut egui::Ui, context: &mut GUIContext) {
+        ui.heading("Path");
+        let pose = context.camera_pose;
+        let path = &mut context.project.camera_path;
+        let duration = path.duration();
+
+        let mut removed = None;
+        for (i, keyframe) in path.keyframes().iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{:.2}s", keyframe.time));
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            path.remove(i);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.keyframe_time)
+                    .speed(0.1)
+                    .range(0.0..=f32::MAX)
+                    .suffix("s"),
+            );
+            if ui.button("Add Keyframe").clicked() {
+                path.insert(self.keyframe_time, pose);
+                self.keyframe_time = path.duration() + 1.0;
+            }
+            if ui.button("Clear").clicked() {
+                path.clear();
+                self.playing = false;
+            }
+        });
+
+        if path.is_empty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            let label = if self.playing { "⏸" } else { "▶" };
+            if ui.button(label).clicked() {
+                self.playing = !self.playing;
+            }
+            let slider = egui::Slider::new(&mut self.time, 0.0..=duration).suffix("s");
+            if ui.add(slider).changed() {
+                self.changed = true;
+            }
+        });
+        views::render_label_and_text(ui, "Duration:", format!("{:.2}s", duration));
+    }
+}
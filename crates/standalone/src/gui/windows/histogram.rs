@@ -0,0 +1,98 @@
+use loupiote_core::LuminanceHistogram;
+
+use crate::gui::views;
+
+#[derive(Default)]
+pub struct HistogramWindow {
+    pub open: bool,
+    /// Last histogram read back by the application.
+    pub histogram: Option<LuminanceHistogram>,
+    log_scale: bool,
+}
+
+impl HistogramWindow {
+    pub fn render(&mut self, egui_ctx: &egui::Context) {
+        let histogram = &self.histogram;
+        let log_scale = &mut self.log_scale;
+        egui::Window::new("Luminance Histogram")
+            .resizable(true)
+            .open(&mut self.open)
+            .show(egui_ctx, |ui| {
+                let Some(histogram) = histogram else {
+                    ui.label("Enable auto-exposure to build the histogram.");
+                    return;
+                };
+                ui.checkbox(log_scale, "Log Scale");
+                Self::render_bars(ui, histogram, *log_scale);
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:.1}", histogram.min_log_luminance));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(format!("{:.1}", histogram.max_log_luminance));
+                    });
+                });
+                ui.separator();
+                views::render_label_and_text(
+                    ui,
+                    "Average (log2):",
+                    format!("{:.2}", histogram.average_log_luminance),
+                );
+                views::render_label_and_text(
+                    ui,
+                    "Exposure:",
+                    format!("{:+.2} EV", histogram.exposure.log2()),
+                );
+                views::render_label_and_text(
+                    ui,
+                    "Below Range:",
+                    format!("{} px", histogram.bins.first().copied().unwrap_or(0)),
+                );
+            });
+    }
+
+    fn render_bars(ui: &mut egui::Ui, histogram: &LuminanceHistogram, log_scale: bool) {
+        let height = |count: u32| {
+            if log_scale {
+                (count as f32).ln_1p()
+            } else {
+                count as f32
+            }
+        };
+        // The first bin is below the histogram range.
+        let bins = histogram.bins.get(1..).unwrap_or_default();
+        let max = bins.iter().map(|&c| height(c)).fold(0.0, f32::max);
+
+        let size = egui::vec2(ui.available_width().max(256.0), 128.0);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        if bins.is_empty() || max <= 0.0 {
+            return;
+        }
+
+        let bar_width = rect.width() / bins.len() as f32;
+        let color = ui.visuals().text_color();
+        for (i, &count) in bins.iter().enumerate() {
+            let bar_height = rect.height() * height(count) / max;
+            let x = rect.left() + i as f32 * bar_width;
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(x, rect.bottom() - bar_height),
+                    egui::pos2(x + bar_width, rect.bottom()),
+                ),
+                0.0,
+                color,
+            );
+        }
+
+        let range = histogram.max_log_luminance - histogram.min_log_luminance;
+        if range > 0.0 {
+            let t = (histogram.average_log_luminance - histogram.min_log_luminance) / range;
+            let x = rect.left() + t.clamp(0.0, 1.0) * rect.width();
+            painter.vline(
+                x,
+                rect.y_range(),
+                egui::Stroke::new(1.0, ui.visuals().warn_fg_color),
+            );
+        }
+    }
+}
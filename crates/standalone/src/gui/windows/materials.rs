@@ -0,0 +1,118 @@
+use loupiote_core::{Material, MaterialHandle, Scene};
+
+use crate::gui::{views, GUIContext};
+
+/// Side of the texture previews, in points.
+const PREVIEW_SIZE: f32 = 96.0;
+
+/// Lists the materials of the scene, with their factors and textures.
+#[derive(Default)]
+pub struct MaterialsWindow {
+    pub open: bool,
+    /// Material changed through the window, applied by the application.
+    pub edited: Option<(MaterialHandle, Material)>,
+    /// Previews of the scene images, created when first displayed.
+    textures: Vec<Option<egui::TextureHandle>>,
+}
+
+impl MaterialsWindow {
+    /// Drops the texture previews, must be called when a scene is loaded.
+    pub fn clear_textures(&mut self) {
+        self.textures.clear();
+    }
+
+    pub fn render(&mut self, context: &GUIContext, egui_ctx: &egui::Context) {
+        let scene = context.scene;
+        let mut open = self.open;
+        egui::Window::new("Materials")
+            .resizable(true)
+            .open(&mut open)
+            .show(egui_ctx, |ui| {
+                if scene.materials.is_empty() {
+                    ui.label("The scene has no material.");
+                    return;
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for index in 0..scene.materials.len() {
+                        egui::CollapsingHeader::new(format!("Material {}", index))
+                            .id_salt(("material", index))
+                            .show(ui, |ui| {
+                                self.render_material(ui, scene, MaterialHandle(index as u32));
+                            });
+                    }
+                });
+            });
+        self.open = open;
+    }
+
+    fn render_material(&mut self, ui: &mut egui::Ui, scene: &Scene, handle: MaterialHandle) {
+        let Some(material) = scene.material(handle) else {
+            return;
+        };
+        let mut material = *material;
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Color:");
+            let mut color = material.color.to_array();
+            if ui.color_edit_button_rgba_unmultiplied(&mut color).changed() {
+                material.color = glam::Vec4::from_array(color);
+                changed = true;
+            }
+        });
+        changed |= ui
+            .add(egui::Slider::new(&mut material.roughness, 0.0..=1.0).text("Roughness"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut material.reflectivity, 0.0..=1.0).text("Reflectivity"))
+            .changed();
+
+        ui.horizontal(|ui| {
+            self.render_texture(ui, scene, "Albedo", material.albedo_texture);
+            self.render_texture(ui, scene, "Metal / Roughness", material.mra_texture);
+        });
+
+        if changed {
+            self.edited = Some((handle, material));
+        }
+    }
+
+    /// Previews the image `index`, material textures index the scene images
+    /// in the same order as the texture atlas.
+    fn render_texture(&mut self, ui: &mut egui::Ui, scene: &Scene, name: &str, index: u32) {
+        ui.vertical(|ui| {
+            ui.label(name);
+            let Some(image) = scene.images.get(index as usize) else {
+                ui.weak("None");
+                return;
+            };
+            let index = index as usize;
+            if self.textures.len() < scene.images.len() {
+                self.textures.resize(scene.images.len(), None);
+            }
+            let texture = self.textures[index].get_or_insert_with(|| {
+                let size = [image.width() as usize, image.height() as usize];
+                // Only 8 bits images are previewed, see the glTF loader.
+                let pixels = if image.data().len() == size[0] * size[1] * 4 {
+                    egui::ColorImage::from_rgba_unmultiplied(size, image.data())
+                } else {
+                    egui::ColorImage::new([1, 1], egui::Color32::BLACK)
+                };
+                ui.ctx().load_texture(
+                    format!("material_texture_{}", index),
+                    pixels,
+                    egui::TextureOptions::LINEAR,
+                )
+            });
+            ui.add(
+                egui::Image::new((texture.id(), texture.size_vec2()))
+                    .max_size(egui::Vec2::splat(PREVIEW_SIZE)),
+            );
+            views::render_label_and_text(
+                ui,
+                "Size:",
+                format!("{}x{}", image.width(), image.height()),
+            );
+        });
+    }
+}
@@ -1,7 +1,19 @@
+mod camera_path;
 mod error;
+mod histogram;
+mod materials;
+mod outliner;
 mod performance_info;
 mod scene_info;
+mod sequence;
+mod timeline;
 
+pub use camera_path::CameraPathWindow;
 pub use error::ErrorWindow;
+pub use histogram::HistogramWindow;
+pub use materials::MaterialsWindow;
+pub use outliner::OutlinerWindow;
 pub use performance_info::PerformanceInfoWindow;
 pub use scene_info::SceneInfoWindow;
+pub use sequence::SequenceWindow;
+pub use timeline::TimelineWindow;
@@ -0,0 +1,308 @@
+use std::collections::HashSet;
+
+use loupiote_core::{InstanceHandle, Scene};
+
+use crate::gui::{views, GUIContext};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// Nodes and instances of the scene, and the transform gizmo of the
+/// selected instance.
+#[derive(Default)]
+pub struct OutlinerWindow {
+    pub open: bool,
+    pub selected: Option<InstanceHandle>,
+    pub gizmo_mode: GizmoMode,
+    /// Transform set through the gizmo, applied by the application.
+    pub edited: Option<(InstanceHandle, glam::Mat4)>,
+    /// Axis of the gizmo being dragged.
+    dragged_axis: Option<usize>,
+}
+
+/// Length of the gizmo axes, in points.
+const GIZMO_SIZE: f32 = 80.0;
+/// Distance to an axis under which it can be dragged, in points.
+const GIZMO_PICK_DISTANCE: f32 = 8.0;
+
+const AXIS_COLORS: [egui::Color32; 3] = [
+    egui::Color32::from_rgb(230, 70, 70),
+    egui::Color32::from_rgb(90, 200, 90),
+    egui::Color32::from_rgb(80, 130, 240),
+];
+
+impl OutlinerWindow {
+    pub fn render(&mut self, context: &mut GUIContext, egui_ctx: &egui::Context) {
+        // The selection might not survive a scene reload.
+        if let Some(handle) = self.selected {
+            if context.scene.instance(handle).is_none() {
+                self.selected = None;
+            }
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Outliner")
+            .resizable(true)
+            .open(&mut open)
+            .show(egui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.gizmo_mode, GizmoMode::Translate, "Translate");
+                    ui.radio_value(&mut self.gizmo_mode, GizmoMode::Rotate, "Rotate");
+                    ui.radio_value(&mut self.gizmo_mode, GizmoMode::Scale, "Scale");
+                });
+                self.render_selection(ui, context.scene);
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.render_tree(ui, context.scene);
+                });
+            });
+        self.open = open;
+
+        self.render_gizmo(context, egui_ctx);
+    }
+
+    fn render_selection(&self, ui: &mut egui::Ui, scene: &Scene) {
+        let Some(transform) = self.selected.and_then(|handle| scene.transform(handle)) else {
+            ui.label("Click an object to select it.");
+            return;
+        };
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let (x, y, z) = rotation.to_euler(glam::EulerRot::XYZ);
+        views::render_label_and_text(
+            ui,
+            "Translation:",
+            format!(
+                "{:.3} {:.3} {:.3}",
+                translation.x, translation.y, translation.z
+            ),
+        );
+        views::render_label_and_text(
+            ui,
+            "Rotation:",
+            format!(
+                "{:.1}° {:.1}° {:.1}°",
+                x.to_degrees(),
+                y.to_degrees(),
+                z.to_degrees()
+            ),
+        );
+        views::render_label_and_text(
+            ui,
+            "Scale:",
+            format!("{:.3} {:.3} {:.3}", scale.x, scale.y, scale.z),
+        );
+    }
+
+    fn render_tree(&mut self, ui: &mut egui::Ui, scene: &Scene) {
+        for (index, node) in scene.nodes.iter().enumerate() {
+            if node.parent.is_none() {
+                self.render_node(ui, scene, index);
+            }
+        }
+        // Instances created without a glTF node.
+        let owned: HashSet<InstanceHandle> = scene
+            .nodes
+            .iter()
+            .flat_map(|node| node.instances.iter().copied())
+            .collect();
+        let orphans: Vec<InstanceHandle> = scene
+            .instances()
+            .filter(|handle| !owned.contains(handle))
+            .collect();
+        if !orphans.is_empty() {
+            egui::CollapsingHeader::new("Instances")
+                .default_open(scene.nodes.is_empty())
+                .show(ui, |ui| {
+                    for handle in orphans {
+                        self.render_instance(ui, scene, handle);
+                    }
+                });
+        }
+    }
+
+    fn render_node(&mut self, ui: &mut egui::Ui, scene: &Scene, index: usize) {
+        let node = &scene.nodes[index];
+        let name = node
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("Node {}", index));
+        if node.children.is_empty() && node.instances.is_empty() {
+            ui.label(name);
+            return;
+        }
+        egui::CollapsingHeader::new(name)
+            .id_salt(("outliner_node", index))
+            .show(ui, |ui| {
+                for &handle in &node.instances {
+                    self.render_instance(ui, scene, handle);
+                }
+                for &child in &node.children {
+                    self.render_node(ui, scene, child);
+                }
+            });
+    }
+
+    fn render_instance(&mut self, ui: &mut egui::Ui, scene: &Scene, handle: InstanceHandle) {
+        let (Some(index), Some(instance)) = (scene.instance_index(handle), scene.instance(handle))
+        else {
+            return;
+        };
+        let label = format!("Instance {} · Material {}", index, instance.material_index);
+        let selected = self.selected == Some(handle);
+        if ui.selectable_label(selected, label).clicked() {
+            self.selected = if selected { None } else { Some(handle) };
+        }
+    }
+
+    /// Draws the axes of the gizmo over the selected instance, and turns
+    /// their drags into [`Self::edited`].
+    fn render_gizmo(&mut self, context: &GUIContext, egui_ctx: &egui::Context) {
+        let Some(handle) = self.selected else {
+            return;
+        };
+        let Some(transform) = context.scene.transform(handle) else {
+            return;
+        };
+        let size = *context.renderer.get_size();
+        let Some(projection) = context.settings.camera.projection(size) else {
+            return;
+        };
+        let screen = egui_ctx.screen_rect();
+        let world_to_screen = |point: glam::Vec3| {
+            let clip = projection * context.view_transform.inverse() * point.extend(1.0);
+            if clip.w <= 0.0 {
+                return None;
+            }
+            let ndc = clip.truncate() / clip.w;
+            Some(egui::pos2(
+                screen.left() + (ndc.x * 0.5 + 0.5) * screen.width(),
+                screen.top() + (0.5 - ndc.y * 0.5) * screen.height(),
+            ))
+        };
+
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let Some(center) = world_to_screen(translation) else {
+            return;
+        };
+        // Scale is applied along the local axes, the other modes use the
+        // world axes.
+        let axes =
+            [glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z].map(|axis| match self.gizmo_mode {
+                GizmoMode::Scale => rotation * axis,
+                _ => axis,
+            });
+        // Screen space motion of a unit move along each axis.
+        let screen_axes = axes.map(|axis| {
+            world_to_screen(translation + axis)
+                .map(|end| end - center)
+                .filter(|v| v.length() > 1e-3)
+        });
+
+        let bounds = egui::Rect::from_center_size(center, egui::Vec2::splat(GIZMO_SIZE * 2.0))
+            .expand(GIZMO_PICK_DISTANCE);
+        let mode = self.gizmo_mode;
+        let mut dragged_axis = self.dragged_axis;
+        let mut edited = None;
+        egui::Area::new(egui::Id::new("outliner_gizmo"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(bounds.min)
+            .constrain(false)
+            .movable(false)
+            .show(egui_ctx, |ui| {
+                // Dragging keeps the pointer away from the camera controls.
+                let (_, painter) = ui.allocate_painter(bounds.size(), egui::Sense::drag());
+                let pointer = ui.input(|i| i.pointer.interact_pos());
+                let pressed = ui.input(|i| i.pointer.primary_pressed());
+                let down = ui.input(|i| i.pointer.primary_down());
+                let delta = ui.input(|i| i.pointer.delta());
+
+                for (axis, screen_axis) in screen_axes.iter().enumerate() {
+                    let Some(screen_axis) = screen_axis else {
+                        continue;
+                    };
+                    let end = center + screen_axis.normalized() * GIZMO_SIZE;
+                    let hovered = pointer
+                        .is_some_and(|p| distance_to_segment(p, center, end) < GIZMO_PICK_DISTANCE);
+                    if pressed && hovered {
+                        dragged_axis = Some(axis);
+                    }
+                    let active = dragged_axis == Some(axis) || hovered;
+                    let color = if active {
+                        egui::Color32::WHITE
+                    } else {
+                        AXIS_COLORS[axis]
+                    };
+                    painter.line_segment([center, end], egui::Stroke::new(3.0, color));
+                    match mode {
+                        GizmoMode::Translate => {
+                            painter.circle_filled(end, 5.0, color);
+                        }
+                        GizmoMode::Rotate => {
+                            painter.circle_stroke(end, 5.0, egui::Stroke::new(2.0, color));
+                        }
+                        GizmoMode::Scale => {
+                            let rect = egui::Rect::from_center_size(end, egui::Vec2::splat(9.0));
+                            painter.rect_filled(rect, 0.0, color);
+                        }
+                    }
+                }
+
+                if !down {
+                    dragged_axis = None;
+                }
+                let Some(axis) = dragged_axis else {
+                    return;
+                };
+                let Some(screen_axis) = screen_axes[axis] else {
+                    return;
+                };
+                if delta == egui::Vec2::ZERO {
+                    return;
+                }
+                // Pointer motion along the axis, in world units.
+                let amount = delta.dot(screen_axis) / screen_axis.length_sq();
+                let result = match mode {
+                    GizmoMode::Translate => glam::Mat4::from_scale_rotation_translation(
+                        scale,
+                        rotation,
+                        translation + axes[axis] * amount,
+                    ),
+                    GizmoMode::Rotate => {
+                        // Motion across the axis turns around it.
+                        let across = egui::vec2(-screen_axis.y, screen_axis.x).normalized();
+                        let angle = delta.dot(across) / GIZMO_SIZE;
+                        glam::Mat4::from_scale_rotation_translation(
+                            scale,
+                            glam::Quat::from_axis_angle(axes[axis], angle) * rotation,
+                            translation,
+                        )
+                    }
+                    GizmoMode::Scale => {
+                        let mut factor = glam::Vec3::ONE;
+                        factor[axis] = (1.0 + amount).max(0.01);
+                        glam::Mat4::from_scale_rotation_translation(
+                            scale * factor,
+                            rotation,
+                            translation,
+                        )
+                    }
+                };
+                edited = Some((handle, result));
+            });
+        self.dragged_axis = dragged_axis;
+        if edited.is_some() {
+            self.edited = edited;
+        }
+    }
+}
+
+fn distance_to_segment(p: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_sq().max(1e-6)).clamp(0.0, 1.0);
+    (p - (a + ab * t)).length()
+}
@@ -0,0 +1,114 @@
+use crate::gui::{views, GUIContext};
+use crate::settings::SequenceFormat;
+
+#[derive(Default)]
+pub struct SequenceWindow {
+    pub open: bool,
+}
+
+impl SequenceWindow {
+    pub fn render(&mut self, context: &mut GUIContext, egui_ctx: &egui::Context) {
+        egui::Window::new("Sequence Export")
+            .resizable(true)
+            .open(&mut self.open)
+            .show(egui_ctx, |ui| {
+                let duration = context
+                    .scene
+                    .animation_duration()
+                    .max(context.project.camera_path.duration());
+                let sequence = &mut context.settings.sequence;
+
+                ui.add(
+                    egui::DragValue::new(&mut sequence.fps)
+                        .speed(1.0)
+                        .range(1.0..=240.0)
+                        .prefix("FPS: "),
+                );
+                ui.horizontal(|ui| {
+                    let end = sequence.end;
+                    ui.add(
+                        egui::DragValue::new(&mut sequence.start)
+                            .speed(0.1)
+                            .range(0.0..=end)
+                            .prefix("Start: ")
+                            .suffix("s"),
+                    );
+                    let start = sequence.start;
+                    ui.add(
+                        egui::DragValue::new(&mut sequence.end)
+                            .speed(0.1)
+                            .range(start..=f32::MAX)
+                            .prefix("End: ")
+                            .suffix("s"),
+                    );
+                    if ui
+                        .add_enabled(duration > 0.0, egui::Button::new("Fit"))
+                        .on_hover_text("Use the duration of the animations and camera path")
+                        .clicked()
+                    {
+                        sequence.start = 0.0;
+                        sequence.end = duration;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    ui.radio_value(&mut sequence.format, SequenceFormat::Png, "PNG");
+                    ui.radio_value(&mut sequence.format, SequenceFormat::Exr, "EXR");
+                    ui.radio_value(
+                        &mut sequence.format,
+                        SequenceFormat::LayeredExr,
+                        "Multi-Layer EXR",
+                    );
+                });
+
+                ui.separator();
+                let convergence = &mut sequence.convergence;
+                ui.add(
+                    egui::DragValue::new(&mut convergence.max_samples)
+                        .speed(1.0)
+                        .range(1..=u32::MAX)
+                        .prefix("Samples: "),
+                );
+                let mut adaptive = convergence.noise_threshold.is_some();
+                if ui.checkbox(&mut adaptive, "Noise Threshold").changed() {
+                    convergence.noise_threshold = if adaptive { Some(0.01) } else { None };
+                }
+                if let Some(threshold) = convergence.noise_threshold.as_mut() {
+                    ui.add(
+                        egui::DragValue::new(threshold)
+                            .speed(0.0001)
+                            .range(0.0..=1.0)
+                            .prefix("Threshold: "),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut convergence.check_interval)
+                            .speed(1.0)
+                            .range(1..=1024)
+                            .prefix("Check Every: ")
+                            .suffix(" frames"),
+                    );
+                }
+
+                ui.separator();
+                views::render_label_and_text(ui, "Frames:", format!("{}", sequence.frame_count()));
+                // @todo: support wasm.
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui.button("Export").clicked() {
+                    let dialog = rfd::AsyncFileDialog::new()
+                        .set_parent(&context.platform.window)
+                        .pick_folder();
+                    let event_loop_proxy = context.event_loop_proxy.clone();
+                    context.executor.spawn_local(async move {
+                        if let Some(folder) = dialog.await {
+                            event_loop_proxy
+                                .send_event(crate::Event::ExportSequence(
+                                    folder.path().to_path_buf(),
+                                ))
+                                .ok();
+                        }
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,60 @@
+use crate::gui::views;
+
+#[derive(Default)]
+pub struct TimelineWindow {
+    pub open: bool,
+    pub playing: bool,
+    /// Current time, in seconds.
+    pub time: f32,
+    pub duration: f32,
+    /// Set when the user moved the cursor, reset by the application.
+    pub changed: bool,
+}
+
+impl TimelineWindow {
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration;
+        self.time = 0.0;
+        self.playing = duration > 0.0;
+        self.changed = true;
+    }
+
+    /// Advances the time by `delta` seconds when playing, looping at the end.
+    ///
+    /// Returns `true` if the scene must be evaluated.
+    pub fn advance(&mut self, delta: f32) -> bool {
+        let changed = std::mem::take(&mut self.changed);
+        if !self.playing || self.duration <= 0.0 {
+            return changed;
+        }
+        self.time = (self.time + delta) % self.duration;
+        true
+    }
+
+    pub fn render(&mut self, egui_ctx: &egui::Context) {
+        let playing = &mut self.playing;
+        let time = &mut self.time;
+        let changed = &mut self.changed;
+        let duration = self.duration;
+        egui::Window::new("Timeline")
+            .resizable(true)
+            .open(&mut self.open)
+            .show(egui_ctx, |ui| {
+                if duration <= 0.0 {
+                    ui.label("No animation");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    let label = if *playing { "⏸" } else { "▶" };
+                    if ui.button(label).clicked() {
+                        *playing = !*playing;
+                    }
+                    let slider = egui::Slider::new(time, 0.0..=duration).suffix("s");
+                    if ui.add(slider).changed() {
+                        *changed = true;
+                    }
+                });
+                views::render_label_and_text(ui, "Duration:", format!("{:.2}s", duration));
+            });
+    }
+}
@@ -18,10 +18,13 @@ impl InputManager {
         state: &ElementState,
     ) -> Option<EditorCommand> {
         // @todo: Mapping should be performed using a config file.
-        match (keycode, state) {
+        match (keycode.as_ref(), state) {
             (Key::Named(NamedKey::Space), ElementState::Pressed) => {
                 Some(EditorCommand::ToggleAccumulation)
             }
+            (Key::Character("f"), ElementState::Pressed) => Some(EditorCommand::FocusUnderCursor),
+            (Key::Character("o"), ElementState::Pressed) => Some(EditorCommand::ToggleCameraMode),
+            (Key::Named(NamedKey::Home), ElementState::Pressed) => Some(EditorCommand::FrameScene),
             _ => None,
         }
     }
@@ -24,6 +24,8 @@ use settings::Settings;
 
 mod errors;
 
+mod exr_layers;
+
 mod logger;
 use logger::log;
 
@@ -34,6 +36,9 @@ mod gui;
 
 mod camera;
 
+mod project;
+use project::*;
+
 pub fn run((event_loop, platform): (winit::event_loop::EventLoop<Event>, Plaftorm)) {
     let event_loop_proxy = event_loop.create_proxy();
 
@@ -93,6 +98,10 @@ pub fn run((event_loop, platform): (winit::event_loop::EventLoop<Event>, Plaftor
 
         last_time: std::time::Instant::now(),
         event_captured: false,
+        cursor_position: None,
+        click_position: None,
+        still_frames: 0,
+        project: Project::default(),
 
         shader_paths: PathBuf::new(),
     };
@@ -115,8 +124,10 @@ pub fn run((event_loop, platform): (winit::event_loop::EventLoop<Event>, Plaftor
 
         // Load helmet and move up.
         loaders::load_gltf_path(gltf_path, &mut scene).unwrap();
-        let model_to_world = scene.blas.instances[1].model_to_world;
-        scene.blas.instances[1].set_transform(
+        let helmet = scene.instances().nth(1).unwrap();
+        let model_to_world = scene.transform(helmet).unwrap();
+        scene.set_transform(
+            helmet,
             glam::Mat4::from_translation(glam::Vec3::new(0.0, 2.0, 0.0)) * model_to_world,
         );
 
@@ -0,0 +1,333 @@
+use std::fmt::Write as _;
+use std::path;
+
+use crate::errors::Error;
+
+/// Camera state that can be saved and restored.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraPose {
+    pub origin: glam::Vec3,
+    pub direction: glam::Vec3,
+    /// Vertical field of view, in radians.
+    pub v_fov: f32,
+}
+
+impl CameraPose {
+    fn to_values(self) -> [f32; 7] {
+        [
+            self.origin.x,
+            self.origin.y,
+            self.origin.z,
+            self.direction.x,
+            self.direction.y,
+            self.direction.z,
+            self.v_fov,
+        ]
+    }
+
+    fn from_values(values: &[f32]) -> Self {
+        CameraPose {
+            origin: glam::Vec3::new(values[0], values[1], values[2]),
+            direction: glam::Vec3::new(values[3], values[4], values[5]).normalize_or(glam::Vec3::Z),
+            v_fov: values[6],
+        }
+    }
+}
+
+pub struct CameraBookmark {
+    pub name: String,
+    pub pose: CameraPose,
+}
+
+pub struct CameraKeyframe {
+    /// Time, in seconds.
+    pub time: f32,
+    pub pose: CameraPose,
+}
+
+/// Camera path going through keyframes, sorted by time.
+///
+/// Positions and directions are interpolated with a Catmull-Rom spline, the
+/// field of view linearly.
+#[derive(Default)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// Adds a keyframe at `time`, replacing any keyframe at the same time.
+    pub fn insert(&mut self, time: f32, pose: CameraPose) {
+        match self.keyframes.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(i) => self.keyframes[i].pose = pose,
+            Err(i) => self.keyframes.insert(i, CameraKeyframe { time, pose }),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.keyframes.len() {
+            self.keyframes.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Pose of the camera at `time`, clamped to the path range.
+    pub fn sample(&self, time: f32) -> Option<CameraPose> {
+        let keys = &self.keyframes;
+        let first = keys.first()?;
+        let last = keys.last()?;
+        if time <= first.time {
+            return Some(first.pose);
+        }
+        if time >= last.time {
+            return Some(last.pose);
+        }
+
+        let next = keys.partition_point(|k| k.time <= time);
+        let (k1, k2) = (&keys[next - 1], &keys[next]);
+        let k0 = &keys[next.saturating_sub(2)];
+        let k3 = &keys[(next + 1).min(keys.len() - 1)];
+        let t = (time - k1.time) / (k2.time - k1.time).max(f32::EPSILON);
+
+        let origin = catmull_rom(
+            k0.pose.origin,
+            k1.pose.origin,
+            k2.pose.origin,
+            k3.pose.origin,
+            t,
+        );
+        let direction = catmull_rom(
+            k0.pose.direction,
+            k1.pose.direction,
+            k2.pose.direction,
+            k3.pose.direction,
+            t,
+        );
+        Some(CameraPose {
+            origin,
+            direction: direction.normalize_or(k1.pose.direction),
+            v_fov: k1.pose.v_fov + (k2.pose.v_fov - k1.pose.v_fov) * t,
+        })
+    }
+}
+
+fn catmull_rom(
+    p0: glam::Vec3,
+    p1: glam::Vec3,
+    p2: glam::Vec3,
+    p3: glam::Vec3,
+    t: f32,
+) -> glam::Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Editor data saved alongside a scene: camera bookmarks and camera path.
+///
+/// Stored as plain text, one entry per line:
+///
+/// ```text
+/// bookmark <ox> <oy> <oz> <dx> <dy> <dz> <v_fov> <name>
+/// keyframe <time> <ox> <oy> <oz> <dx> <dy> <dz> <v_fov>
+/// ```
+#[derive(Default)]
+pub struct Project {
+    pub bookmarks: Vec<CameraBookmark>,
+    pub camera_path: CameraPath,
+}
+
+impl Project {
+    pub fn load<P: AsRef<path::Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|_| Error::FileNotFound(path.display().to_string()))?;
+        Self::parse(&text)
+    }
+
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_text())
+            .map_err(|e| Error::FileWrite(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut project = Project::default();
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |reason: &str| {
+                Error::InvalidProject(format!("line {}: {}", line_index + 1, reason))
+            };
+            let (kind, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let mut tokens = rest.split_whitespace();
+            let (count, keep_rest) = match kind {
+                "bookmark" => (7, true),
+                "keyframe" => (8, false),
+                _ => return Err(invalid(&format!("unknown entry '{}'", kind))),
+            };
+            let values = tokens
+                .by_ref()
+                .take(count)
+                .map(|v| match v.parse::<f32>() {
+                    Ok(v) if v.is_finite() => Ok(v),
+                    _ => Err(invalid(&format!("invalid number '{}'", v))),
+                })
+                .collect::<Result<Vec<f32>, _>>()?;
+            if values.len() != count {
+                return Err(invalid(&format!(
+                    "expected {} values, found {}",
+                    count,
+                    values.len()
+                )));
+            }
+            if !keep_rest && tokens.next().is_some() {
+                return Err(invalid("unexpected trailing values"));
+            }
+            if keep_rest {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                project.bookmarks.push(CameraBookmark {
+                    name,
+                    pose: CameraPose::from_values(&values),
+                });
+            } else {
+                project
+                    .camera_path
+                    .insert(values[0], CameraPose::from_values(&values[1..]));
+            }
+        }
+        Ok(project)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# loupiote project\n");
+        for bookmark in &self.bookmarks {
+            text.push_str("bookmark");
+            for v in bookmark.pose.to_values() {
+                write!(text, " {}", v).unwrap();
+            }
+            writeln!(text, " {}", bookmark.name).unwrap();
+        }
+        for keyframe in self.camera_path.keyframes() {
+            write!(text, "keyframe {}", keyframe.time).unwrap();
+            for v in keyframe.pose.to_values() {
+                write!(text, " {}", v).unwrap();
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pose(x: f32) -> CameraPose {
+        CameraPose {
+            origin: glam::Vec3::new(x, 1.5, -2.25),
+            direction: glam::Vec3::new(0.0, 0.6, 0.8),
+            v_fov: 0.75,
+        }
+    }
+
+    #[test]
+    fn save_then_parse_round_trips() {
+        let mut project = Project::default();
+        project.bookmarks.push(CameraBookmark {
+            name: String::from("Front door"),
+            pose: pose(0.1),
+        });
+        project.bookmarks.push(CameraBookmark {
+            name: String::new(),
+            pose: pose(-3.0),
+        });
+        project.camera_path.insert(2.5, pose(1.0 / 3.0));
+        project.camera_path.insert(0.0, pose(4.0));
+
+        let path = std::env::temp_dir().join(format!("loupiote_{}.project", std::process::id()));
+        project.save(&path).unwrap();
+        let loaded = Project::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.bookmarks.len(), 2);
+        for (a, b) in loaded.bookmarks.iter().zip(&project.bookmarks) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.pose, b.pose);
+        }
+        let keyframes = loaded.camera_path.keyframes();
+        assert_eq!(keyframes.len(), 2);
+        for (a, b) in keyframes.iter().zip(project.camera_path.keyframes()) {
+            assert_eq!(a.time, b.time);
+            assert_eq!(a.pose, b.pose);
+        }
+        assert_eq!(loaded.to_text(), project.to_text());
+    }
+
+    #[test]
+    fn save_reports_write_errors() {
+        let path = std::env::temp_dir().join("loupiote_missing_directory/project.txt");
+        assert!(matches!(
+            Project::default().save(path),
+            Err(Error::FileWrite(_))
+        ));
+    }
+
+    #[test]
+    fn parse_skips_comments_and_blank_lines() {
+        let project = Project::parse("# comment\n\n  \nkeyframe 1 0 0 0 0 0 1 0.5\n").unwrap();
+        assert_eq!(project.camera_path.keyframes().len(), 1);
+        assert!(project.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_lines() {
+        let invalid = [
+            "keyframe 1 0 0 0 0 0 1 0.5 7",
+            "keyframe 1 0 0 0 0 0 1",
+            "keyframe inf 0 0 0 0 0 1 0.5",
+            "bookmark 0 0 NaN 0 0 1 0.5 name",
+            "bookmark 0 0 zero 0 0 1 0.5",
+            "bookmark",
+            "camera 0",
+        ];
+        for text in invalid {
+            assert!(
+                matches!(Project::parse(text), Err(Error::InvalidProject(_))),
+                "parsed '{}'",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        let text = "# comment\nkeyframe 1 0 0 0 0 0 1 0.5\nkeyframe 2 0 0 0 0 0 1\n";
+        match Project::parse(text) {
+            Err(Error::InvalidProject(reason)) => {
+                assert_eq!(reason, "line 3: expected 8 values, found 7")
+            }
+            _ => panic!("parsed a truncated keyframe"),
+        }
+    }
+}
@@ -1,17 +1,99 @@
-use loupiote_core::BlitMode;
+use loupiote_core::{BlitMode, BounceSettings, CameraParams, Convergence, TonemapSettings};
+
+use crate::camera::CameraMode;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SequenceFormat {
+    Png,
+    /// Linear radiance, as 32 bits floats.
+    Exr,
+    /// Linear radiance and the passes of [`crate::exr_layers::LAYERS`].
+    LayeredExr,
+}
+
+impl SequenceFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SequenceFormat::Png => "png",
+            SequenceFormat::Exr | SequenceFormat::LayeredExr => "exr",
+        }
+    }
+}
+
+/// Frames exported by [`crate::ApplicationContext::export_sequence`].
+#[derive(Copy, Clone)]
+pub struct SequenceSettings {
+    pub fps: f32,
+    /// Time of the first frame, in seconds.
+    pub start: f32,
+    /// Time of the last frame, in seconds.
+    pub end: f32,
+    pub format: SequenceFormat,
+    pub convergence: Convergence,
+}
+
+impl SequenceSettings {
+    pub fn frame_count(&self) -> u32 {
+        if self.fps <= 0.0 || self.end < self.start {
+            return 0;
+        }
+        ((self.end - self.start) * self.fps).floor() as u32 + 1
+    }
+}
+
+impl Default for SequenceSettings {
+    fn default() -> Self {
+        Self {
+            fps: 30.0,
+            start: 0.0,
+            end: 1.0,
+            format: SequenceFormat::Png,
+            convergence: Convergence::default(),
+        }
+    }
+}
 
 pub struct Settings {
     pub accumulate: bool,
     pub use_blue_noise: bool,
     pub blit_mode: BlitMode,
+    pub bounces: BounceSettings,
+    /// Ratio between the render and window sizes.
+    pub resolution_scale: f32,
+    /// Resolution scale used while the camera moves, if any.
+    pub moving_resolution_scale: Option<f32>,
+    /// Reconstructs the full resolution image from the downsampled render.
+    pub temporal_upscaling: bool,
+    pub tonemapping: TonemapSettings,
+    /// Name of the grading LUT, if any.
+    pub lut: Option<String>,
+    pub camera: CameraParams,
+    pub camera_mode: CameraMode,
+    pub sequence: SequenceSettings,
 }
 
 impl Settings {
+    pub fn resolution_scale(&self, camera_moving: bool) -> f32 {
+        match self.moving_resolution_scale {
+            Some(scale) if camera_moving => scale,
+            _ => self.resolution_scale,
+        }
+    }
+
     pub fn new() -> Self {
         Self {
             accumulate: false,
             use_blue_noise: false,
             blit_mode: BlitMode::Pahtrace,
+            bounces: BounceSettings::default(),
+            resolution_scale: 0.5,
+            moving_resolution_scale: None,
+            temporal_upscaling: false,
+            tonemapping: TonemapSettings::default(),
+            lut: None,
+            camera: CameraParams::default(),
+            camera_mode: CameraMode::Fly,
+            sequence: SequenceSettings::default(),
         }
     }
 }